@@ -1,12 +1,25 @@
-use crate::{user_input, ArgsError, Filters, OutputFormatParseError, Year};
+use crate::output::Pager;
+use crate::{
+    output, persistent, user_input, ArgsError, Filters, OnDiskConfig,
+    OutputFormatParseError, ProfileNameError, Year,
+};
 use clap::{Arg, ArgAction, ArgMatches, Command};
 
-use crate::omdb::MediaType;
-use clap::builder::NonEmptyStringValueParser;
-use std::fmt::Write;
-use std::io::{stdin, stdout};
+use crate::omdb::{
+    Genre, MediaType, SortKey, DEFAULT_MAX_REQUESTS_PER_SEARCH,
+    DEFAULT_TIMEOUT_SECS,
+};
+use clap::builder::{NonEmptyStringValueParser, RangedU64ValueParser};
+use itertools::Itertools;
+use log::LevelFilter;
+use minreq::Proxy;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::io::{stdin, stdout, Read};
 use std::ops::BitOr;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::{env, fmt};
 use trim_in_place::TrimInPlace;
 
 #[derive(Debug)]
@@ -18,13 +31,85 @@ pub struct RuntimeConfig {
     pub format: OutputFormat,
     pub api_key: Option<String>,
     pub print_url: bool,
+    pub merge_ids: Vec<String>,
+    pub batch: Vec<String>,
+    pub output: Option<PathBuf>,
+    pub append: bool,
+    pub delimiter: char,
+    pub compact: bool,
+    pub details: bool,
+    pub fields: Option<Vec<output::OutputField>>,
+    pub no_save: bool,
+    pub results_format: Option<String>,
+    pub resolve_years: bool,
+    pub interactive_paginate: bool,
+    pub max_requests: usize,
+    pub always_tui: bool,
+    pub benchmark: bool,
+    pub user_agent: Option<String>,
+    pub timeout: u64,
+    pub proxy: Option<Proxy>,
+    pub refresh: bool,
+    pub reconfigure: bool,
+    pub dry_run: bool,
+    pub ignore_quota: bool,
+    pub open: bool,
+    pub full_plot: bool,
+    pub sort: SortKey,
+    pub fuzzy: bool,
+    pub exact: bool,
+    pub clipboard: bool,
+    pub favourite: bool,
+    pub list_favourites: bool,
+    pub pager: Pager,
+    pub verbosity: LevelFilter,
 }
 
 impl RuntimeConfig {
-    pub fn new() -> Result<Self, ArgsError> {
-        RuntimeConfig::process_matches(
-            &mut RuntimeConfig::create_clap_app().get_matches(),
-        )
+    // The config is only loaded (with load_config) if --no-config isn't
+    // given, and is handed back so it can be used elsewhere
+    pub fn new<'a>(
+        load_config: impl FnOnce() -> Option<OnDiskConfig<'a>>,
+    ) -> Result<(Self, Option<OnDiskConfig<'a>>), ArgsError> {
+        let mut clap_matches = RuntimeConfig::create_clap_app().get_matches();
+        // Nothing else matters when benchmarking, and it shouldn't prompt for
+        // a search term
+        if clap_matches.get_flag("benchmark") {
+            let runtime_config = RuntimeConfig {
+                benchmark: true,
+                ..Default::default()
+            };
+            return Ok((runtime_config, None));
+        }
+        let disk_config =
+            RuntimeConfig::disk_config(&clap_matches, load_config);
+        let runtime_config = RuntimeConfig::process_matches(
+            &mut clap_matches,
+            disk_config.as_ref(),
+        )?;
+        Ok((runtime_config, disk_config))
+    }
+
+    // The TUI is skipped when there's only one result, unless --always-tui
+    pub fn use_tui(&self, number_of_results: usize) -> bool {
+        self.interactive && (number_of_results > 1 || self.always_tui)
+    }
+
+    fn disk_config<'a>(
+        clap_matches: &ArgMatches,
+        load_config: impl FnOnce() -> Option<OnDiskConfig<'a>>,
+    ) -> Option<OnDiskConfig<'a>> {
+        if clap_matches.get_flag("no-config") {
+            None
+        } else {
+            if let Some(path) = clap_matches.get_one::<PathBuf>("config") {
+                persistent::set_config_path(path.clone());
+            }
+            if let Some(profile) = clap_matches.get_one::<String>("profile") {
+                persistent::set_profile(profile.clone());
+            }
+            load_config()
+        }
     }
 
     fn create_clap_app() -> Command {
@@ -48,6 +133,31 @@ impl RuntimeConfig {
                 .help("Print the full IMDb URL instead of just the ID")
                 .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("merge-stdin")
+                    .long("merge-stdin")
+                    .help("Merge IMDb IDs piped via stdin with the search results")
+                    .long_help(
+                        "Merge IMDb IDs piped via stdin (one per line) with the search results\n\
+                    Piped IDs come first in their original order, followed by \
+                    any new search results. Duplicates are removed",
+                    )
+                    .requires("search_term")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("batch")
+                    .long("batch")
+                    .help("Look up each title piped via stdin, one per line")
+                    .long_help(
+                        "Look up each title piped via stdin (one per line), \
+                    printing each title and the ID of its first result\n\
+                    Titles with no results get NOT_FOUND instead of an ID. \
+                    Always non-interactive",
+                    )
+                    .conflicts_with_all(["search_term", "merge-stdin"])
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("number_of_results")
                     .short('r')
@@ -62,7 +172,8 @@ impl RuntimeConfig {
                     .short('t')
                     .long("type")
                     .help("Filters results to a specific media type (movie or series)")
-                    .long_help("Filters results to a specific media type (movie or series). Can be given multiple times")
+                    .long_help("Filters results to a specific media type (movie or series). Can be given multiple times\n\
+                    Several types can also be given separated by commas, e.g. movie,series")
                     .num_args(1)
                     .action(ArgAction::Append)
                     .value_parser(MediaType::from_str),
@@ -75,13 +186,129 @@ impl RuntimeConfig {
                     .long_help(
                         "Filters results to a specific year, or range of years\n\
                     Media which has no year specified will always be included\n\
-                    Ranges are fully inclusive\n\
-                    Examples: 2021, 1990-2000, 2000- (2000 onwards), \
-                    -2000 (before 2000)",
+                    Ranges are fully inclusive, and several can be given separated by commas\n\
+                    Examples: 2021, 1990-2000, 2000- or 2000+ (2000 onwards), \
+                    -2000 (before 2000), 1990s (1990-1999), 1990-1995,2010-",
                     )
                     .num_args(1)
                     .allow_hyphen_values(true)
-                    .value_parser(Year::from_str),
+                    .value_parser(Year::parse_list),
+            )
+            .arg(
+                Arg::new("min_rating")
+                    .long("min-rating")
+                    .help("Only show media with at least this IMDb rating (out of 10)")
+                    .long_help(
+                        "Only show media with at least this IMDb rating (out of 10)\n\
+                    Media without a rating will be excluded\n\
+                    This makes an extra request per result checked",
+                    )
+                    .num_args(1)
+                    .value_parser(clap::value_parser!(f32)),
+            )
+            .arg(
+                Arg::new("sort")
+                    .long("sort")
+                    .help("The order to show results in (relevance, year, year-desc, or title)")
+                    .long_help(
+                        "The order to show results in\n\
+                    One of: relevance (default), year, year-desc, title\n\
+                    Results with the same year keep their order by relevance",
+                    )
+                    .num_args(1)
+                    .value_parser(SortKey::from_str),
+            )
+            .arg(
+                Arg::new("exact")
+                    .long("exact")
+                    .help("Only show media titled exactly the search term (ignoring case)")
+                    .conflicts_with("batch")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("fuzzy")
+                    .long("fuzzy")
+                    .help("Put the results with titles closest to the search term first")
+                    .long_help(
+                        "Put the results with titles closest to the search term \
+                    first, instead of going by OMDb's relevance\n\
+                    Exact matches always come first. With --sort, results that \
+                    tie keep this order",
+                    )
+                    .conflicts_with("batch")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("genre")
+                    .long("genre")
+                    .help("Only show media in this genre. Can be given multiple times")
+                    .long_help(
+                        "Only show media in this genre. Can be given multiple times\n\
+                    Media in any of the genres given is shown. Only genres IMDb uses are accepted, e.g. comedy, sci-fi\n\
+                    This makes an extra request per result checked",
+                    )
+                    .num_args(1)
+                    .action(ArgAction::Append)
+                    .value_parser(Genre::from_str),
+            )
+            .arg(
+                Arg::new("language")
+                    .long("language")
+                    .help("Only show media in this language. Can be given multiple times")
+                    .long_help(
+                        "Only show media in this language. Can be given multiple times\n\
+                    Media in any of the languages given is shown\n\
+                    This makes an extra request per result checked",
+                    )
+                    .num_args(1)
+                    .action(ArgAction::Append)
+                    .value_parser(NonEmptyStringValueParser::new()),
+            )
+            .arg(
+                Arg::new("resolve-years")
+                    .long("resolve-years")
+                    .help("Look up the full details of ongoing series to get their exact years")
+                    .long_help(
+                        "Look up the full details of ongoing series to get their exact years\n\
+                    This makes an extra request per series shown, so is off by default",
+                    )
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("benchmark")
+                    .long("benchmark")
+                    .help("Time merging synthetic search results (for development)")
+                    .hide(true)
+                    .exclusive(true)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .help("Explain what's being done, more so if given twice (-vv)")
+                    .long_help(
+                        "Explain what's being done, more so if given twice (-vv)\n\
+                    -vv includes every request sent to OMDb (without your API key)",
+                    )
+                    .action(ArgAction::Count),
+            )
+            .arg(
+                Arg::new("always-tui")
+                    .long("always-tui")
+                    .help("Show the interactive list even if there's only one result")
+                    .conflicts_with("non-interactive")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("interactive-paginate")
+                    .long("interactive-paginate")
+                    .help("Allow loading more results from within the interactive list (press m)")
+                    .long_help(
+                        "Allow loading more results from within the interactive list (press m)\n\
+                    Each extra page of results makes more requests, so is off by default",
+                    )
+                    .action(ArgAction::SetTrue),
             )
             .arg(
                 Arg::new("format")
@@ -91,11 +318,91 @@ impl RuntimeConfig {
                     .long_help(
                         "Change output format to desired standard\n\
                     Formats are only available if you opted-IN at installation\n\
-                    All the formats imdb-id can support are: json, yaml",
+                    All the formats imdb-id can support are: json, ndjson, tsv, csv, table, markdown, yaml, html, toml",
                     )
                     .num_args(1)
                     .value_parser(OutputFormat::from_str),
             )
+            .arg(
+                Arg::new("delimiter")
+                    .long("delimiter")
+                    .help("The character to separate fields with in tsv output (default: tab)")
+                    .num_args(1)
+                    .value_parser(clap::value_parser!(char)),
+            )
+            .arg(
+                Arg::new("details")
+                    .long("details")
+                    .help("Output the full details of each result, instead of just its title, year, ID, and type")
+                    .long_help(
+                        "Output the full details of each result, instead of just \
+                    its title, year, ID, and type\n\
+                    Only supported by json, ndjson, and yaml. This makes an \
+                    extra request per result",
+                    )
+                    .conflicts_with_all(["batch", "list-favourites"])
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("fields")
+                    .long("fields")
+                    .help("Only output these fields, separated by commas")
+                    .long_help(
+                        "Only output these fields, separated by commas\n\
+                    Fields available are title, year, imdb_id, media_type, and \
+                    poster\n\
+                    Example: title,year,imdb_id",
+                    )
+                    .num_args(1)
+                    .conflicts_with_all(["batch", "details"]),
+            )
+            .arg(
+                Arg::new("compact")
+                    .long("compact")
+                    .help("Put json output all on one line, instead of pretty-printing it")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Write output to the given file instead of stdout")
+                    .long_help(
+                        "Write output to the given file instead of stdout\n\
+                    The file is created if needed, and overwritten unless --append is given",
+                    )
+                    .num_args(1)
+                    .value_parser(clap::value_parser!(PathBuf)),
+            )
+            .arg(
+                Arg::new("append")
+                    .long("append")
+                    .help("Add to the end of the --output file instead of overwriting it")
+                    .long_help(
+                        "Add to the end of the --output file instead of overwriting it\n\
+                    Only supported by formats that can be added to, like the default",
+                    )
+                    .requires("output")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("pager")
+                    .long("pager")
+                    .help("Always show output through your pager ($PAGER)")
+                    .long_help(
+                        "Always show output through your pager ($PAGER)\n\
+                    By default, the pager is used when the output is too long \
+                    to fit in the terminal",
+                    )
+                    .conflicts_with("no-pager")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("no-pager")
+                    .long("no-pager")
+                    .help("Never show output through your pager")
+                    .action(ArgAction::SetTrue),
+            )
             .arg(
                 Arg::new("search_term")
                     .help("The title of the movie/show you're looking for")
@@ -107,25 +414,261 @@ impl RuntimeConfig {
                     .long("api-key")
                     .alias("apikey")
                     .help("Your OMDb API key")
-                    .long_help("Your OMDb API key (overrides saved value if present)")
+                    .long_help("Your OMDb API key (overrides IMDB_ID_API_KEY and the saved value if present)")
+                    .num_args(1)
+                    .value_parser(NonEmptyStringValueParser::new()),
+            )
+            .arg(
+                Arg::new("results_format")
+                    .long("results-format")
+                    .help("How each result is shown in the interactive list")
+                    .long_help(
+                        "How each result is shown in the interactive list \
+                    (overrides tui.list_format in the config if present)\n\
+                    Fields available are {title}, {year}, {type}, and {id}\n\
+                    Example: \"{year} - {title} [{type}]\"",
+                    )
+                    .num_args(1)
+                    .value_parser(NonEmptyStringValueParser::new()),
+            )
+            .arg(
+                Arg::new("user_agent")
+                    .long("user-agent")
+                    .help("The User-Agent to send with requests (default: imdb-id/<version>)")
+                    .num_args(1)
+                    .value_parser(NonEmptyStringValueParser::new()),
+            )
+            .arg(
+                Arg::new("proxy")
+                    .long("proxy")
+                    .help("Send requests through this HTTP proxy (overrides HTTPS_PROXY/HTTP_PROXY if set)")
+                    .long_help(
+                        "Send requests through this HTTP proxy (overrides HTTPS_PROXY/HTTP_PROXY if set)\n\
+                    Format: [http://][user[:password]@]host[:port], the port defaults to 8080",
+                    )
                     .num_args(1)
                     .value_parser(NonEmptyStringValueParser::new()),
             )
+            .arg(
+                Arg::new("max_requests")
+                    .long("max-requests")
+                    .help("The most requests to make for a search (default: 10)")
+                    .long_help(
+                        "The most requests to make for a search (default: 10)\n\
+                    Filtering by several years or types makes a request for each, up to this limit",
+                    )
+                    .num_args(1)
+                    .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+            )
+            .arg(
+                Arg::new("timeout")
+                    .long("timeout")
+                    .help("How many seconds to wait for a response from OMDb (default: 30)")
+                    .num_args(1)
+                    .value_parser(clap::value_parser!(u64).range(1..)),
+            )
+            .arg(
+                Arg::new("clipboard")
+                    .short('C')
+                    .long("clipboard")
+                    .help("Copy the chosen ID (or URL with --print-url) to the clipboard")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("full_plot")
+                    .long("full-plot")
+                    .help("Show the full plot of media in the interactive list, instead of a summary")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("favourite")
+                    .short('b')
+                    .long("favourite")
+                    .help("Add the chosen result to your favourites")
+                    .conflicts_with_all(["batch", "list-favourites"])
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("list-favourites")
+                    .long("list-favourites")
+                    .help("Print your favourites (in the --format given) instead of searching")
+                    .conflicts_with_all(["search_term", "batch", "merge-stdin"])
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("open")
+                    .long("open")
+                    .help("Open the chosen result's IMDb page in your browser")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("refresh")
+                    .long("refresh")
+                    .help("Fetch details of media again, instead of using the ones saved from previous runs")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("reconfigure")
+                    .long("reconfigure")
+                    .help("Check the API key with OMDb, even if it was checked in the last day")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("dry-run")
+                    .long("dry-run")
+                    .help("Print the requests a search would send to OMDb, without sending any")
+                    .long_help(
+                        "Print the requests a search would send to OMDb, without sending any\n\
+                    Only the first page of each search is shown, as any more depend on OMDb's responses. Your API key is hidden",
+                    )
+                    .conflicts_with_all(["batch", "list-favourites"])
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("ignore-quota")
+                    .long("ignore-quota")
+                    .help("Make requests even if today's request budget has been used up")
+                    .long_help(
+                        "Make requests even if today's request budget has been used up\n\
+                    Requests are still counted towards the budget",
+                    )
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("no-save")
+                    .long("no-save")
+                    .help("Don't save the API key to the config file")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .help("Use this config file instead of the default one")
+                    .num_args(1)
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .conflicts_with("no-config"),
+            )
+            .arg(
+                Arg::new("profile")
+                    .long("profile")
+                    .help("Use the config file of this profile, with its own API key and defaults")
+                    .long_help(
+                        "Use the config file of this profile, with its own API key and defaults\n\
+                    Saved next to the default config file, as imdb-id.<profile>.json",
+                    )
+                    .num_args(1)
+                    .value_parser(profile_name)
+                    .conflicts_with_all(["config", "no-config"]),
+            )
+            .arg(
+                Arg::new("no-config")
+                    .long("no-config")
+                    .help("Ignore the config file entirely (implies --no-save)")
+                    .long_help(
+                        "Ignore the config file entirely (implies --no-save)\n\
+                    Useful for ruling out your saved settings as the cause of a problem",
+                    )
+                    .action(ArgAction::SetTrue),
+            )
             .after_long_help("ENVIRONMENT VARIABLES:\n    \
             IMDB_ID_MAX_REQUESTS_PER_SEARCH\n            \
-            Adjusts the limit on the number \
-            of requests per search. Default is 10\
+            Adjusts the limit on the number of requests \
+            per search, if --max-requests isn't given\n    \
+            IMDB_ID_API_KEY\n            \
+            Your OMDb API key, if --api-key isn't given \
+            (overrides the saved value if present)\n    \
+            IMDB_ID_USER_AGENT\n            \
+            The User-Agent to send with requests, \
+            if --user-agent isn't given\n    \
+            IMDB_ID_TIMEOUT\n            \
+            How many seconds to wait for a response \
+            from OMDb, if --timeout isn't given\n    \
+            IMDB_ID_CONFIG\n            \
+            The absolute path of the config file to \
+            use, if --config isn't given\
             ")
     }
 
     fn process_matches(
         clap_matches: &mut ArgMatches,
+        disk_config: Option<&OnDiskConfig>,
     ) -> Result<Self, ArgsError> {
+        let min_search_length = disk_config
+            .map(|cfg| cfg.min_search_length)
+            .unwrap_or(OnDiskConfig::DEFAULT_MIN_SEARCH_LENGTH);
+
+        // Flags take priority over the config's defaults, which take priority
+        // over the built-in ones
         let format = clap_matches
             .remove_one::<OutputFormat>("format")
+            .or(disk_config.and_then(|cfg| cfg.default_format))
             .unwrap_or_default();
 
-        let mut interactive = !clap_matches.get_flag("non-interactive");
+        let delimiter = match clap_matches.remove_one::<char>("delimiter") {
+            Some(_) if !matches!(format, OutputFormat::Tsv) => {
+                return Err(ArgsError::DelimiterWithoutTsv);
+            },
+            Some('\n' | '\r') => return Err(ArgsError::NewlineDelimiter),
+            Some(delimiter) => delimiter,
+            None => output::DEFAULT_DELIMITER,
+        };
+
+        let details = clap_matches.get_flag("details");
+        if details && !format.is_nestable() {
+            return Err(ArgsError::DetailsFormat(format));
+        }
+
+        let fields = match clap_matches.remove_one::<String>("fields") {
+            Some(_) if matches!(format, OutputFormat::Human) => {
+                return Err(ArgsError::FieldsFormat(format));
+            },
+            #[cfg(feature = "html")]
+            Some(_) if matches!(format, OutputFormat::Html) => {
+                return Err(ArgsError::FieldsFormat(format));
+            },
+            Some(fields) => Some(output_fields(&fields)?),
+            None => None,
+        };
+
+        let compact = clap_matches.get_flag("compact");
+        if compact && !matches!(format, OutputFormat::Json) {
+            return Err(ArgsError::CompactWithoutJson);
+        }
+
+        let output = clap_matches.remove_one::<PathBuf>("output");
+
+        let mut pager = if clap_matches.get_flag("pager") {
+            Pager::Always
+        } else if clap_matches.get_flag("no-pager") {
+            Pager::Never
+        } else {
+            Pager::Auto
+        };
+        // Machine readable formats and files are never paged
+        if output.is_some() || !format.is_pageable() {
+            pager = Pager::Never;
+        }
+        if cfg!(not(test)) {
+            use std::io::IsTerminal;
+            if !stdout().is_terminal() {
+                pager = Pager::Never;
+            }
+        }
+        let append = clap_matches.get_flag("append");
+        if append && !format.is_appendable() {
+            return Err(ArgsError::NotAppendable(format));
+        }
+
+        let batch_mode = clap_matches.get_flag("batch");
+        let list_favourites = clap_matches.get_flag("list-favourites");
+        if batch_mode
+            && !matches!(format, OutputFormat::Human | OutputFormat::Json)
+        {
+            return Err(ArgsError::BatchFormat(format));
+        }
+
+        let mut interactive =
+            !clap_matches.get_flag("non-interactive") && !batch_mode;
         // TTY checks are disabled for testing
         if cfg!(not(test)) {
             use std::io::IsTerminal;
@@ -137,38 +680,83 @@ impl RuntimeConfig {
             if interactive || !matches!(format, OutputFormat::Human) {
                 clap_matches
                     .remove_one::<usize>("number_of_results")
+                    .or(disk_config.and_then(|cfg| cfg.default_results))
                     .unwrap_or(RuntimeConfig::default().number_of_results)
             } else {
                 1
             };
 
-        let api_key = clap_matches.remove_one::<String>("api_key");
+        let api_key = api_key(
+            clap_matches.remove_one::<String>("api_key"),
+            env::var("IMDB_ID_API_KEY").ok(),
+        );
 
         let types = clap_matches
             .remove_many::<MediaType>("filter_type")
             .map(|mts| mts.reduce(BitOr::bitor).unwrap())
+            .or(disk_config.and_then(|cfg| cfg.default_media_type))
             .unwrap_or(MediaType::ALL);
 
         // Match used so ? can be used
-        let years = clap_matches.remove_one::<Year>("filter_year");
+        let years = clap_matches
+            .remove_one::<Vec<Year>>("filter_year")
+            .unwrap_or_default();
+
+        let min_rating = match clap_matches.remove_one::<f32>("min_rating") {
+            Some(rating) if !(0.0..=10.0).contains(&rating) => {
+                return Err(ArgsError::RatingOutOfRange(rating));
+            },
+            rating => rating,
+        };
 
-        let filters = Filters { types, years };
+        let genres = clap_matches
+            .remove_many::<Genre>("genre")
+            .map(Iterator::collect)
+            .unwrap_or_default();
+
+        let languages = clap_matches
+            .remove_many::<String>("language")
+            .map(|languages| languages.map(|l| l.trim().to_owned()).collect())
+            .unwrap_or_default();
+
+        let filters = Filters {
+            types,
+            years,
+            min_rating,
+            genres,
+            languages,
+        };
 
         let search_term =
             match clap_matches.remove_many::<String>("search_term") {
-                Some(mut words) => {
-                    let mut search_term = words.next().unwrap();
-                    search_term.trim_in_place();
-                    words.for_each(|word| {
-                        write!(search_term, " {} ", word.trim()).unwrap();
-                    });
-                    // Remove trailing extra space
-                    search_term.pop();
-                    search_term
+                Some(words) => {
+                    let search_term = words
+                        .map(|mut word| {
+                            word.trim_in_place();
+                            word
+                        })
+                        .join(" ");
+                    check_search_term_length(search_term, min_search_length)?
                 },
+                // The titles come from stdin instead
+                None if batch_mode || list_favourites => String::new(),
                 None => {
                     if cfg!(not(test)) {
-                        user_input::cli::get_search_term(filters.types)?
+                        use std::io::IsTerminal;
+                        if stdin().is_terminal() {
+                            user_input::cli::get_search_term(
+                                filters.types,
+                                min_search_length,
+                                disk_config
+                                    .map(|cfg| cfg.recent_searches.as_slice())
+                                    .unwrap_or_default(),
+                            )?
+                        } else {
+                            check_search_term_length(
+                                search_term_from_reader(stdin().lock())?,
+                                min_search_length,
+                            )?
+                        }
                     } else {
                         String::new()
                     }
@@ -176,6 +764,67 @@ impl RuntimeConfig {
             };
 
         let print_url = clap_matches.get_flag("print-url");
+        // Don't overwrite a config that was never read
+        let no_save = clap_matches.get_flag("no-save")
+            || clap_matches.get_flag("no-config");
+        let resolve_years = clap_matches.get_flag("resolve-years");
+        let interactive_paginate =
+            clap_matches.get_flag("interactive-paginate");
+        let always_tui = clap_matches.get_flag("always-tui");
+        let verbosity = verbosity(clap_matches.get_count("verbose"));
+        let refresh = clap_matches.get_flag("refresh");
+        let reconfigure = clap_matches.get_flag("reconfigure");
+        let dry_run = clap_matches.get_flag("dry-run");
+        let ignore_quota = clap_matches.get_flag("ignore-quota");
+        let open = clap_matches.get_flag("open");
+        let full_plot = clap_matches.get_flag("full_plot");
+        let sort = clap_matches
+            .remove_one::<SortKey>("sort")
+            .unwrap_or_default();
+        let fuzzy = clap_matches.get_flag("fuzzy");
+        let exact = clap_matches.get_flag("exact");
+        let clipboard = clap_matches.get_flag("clipboard");
+        let favourite = clap_matches.get_flag("favourite");
+        let results_format =
+            clap_matches.remove_one::<String>("results_format");
+        let user_agent = user_agent(
+            clap_matches.remove_one::<String>("user_agent"),
+            env::var("IMDB_ID_USER_AGENT").ok(),
+        )?;
+        let max_requests = max_requests(
+            clap_matches.remove_one::<usize>("max_requests"),
+            env::var("IMDB_ID_MAX_REQUESTS_PER_SEARCH").ok(),
+        );
+        let timeout = timeout(
+            clap_matches.remove_one::<u64>("timeout"),
+            env::var("IMDB_ID_TIMEOUT").ok(),
+        )?;
+        // OMDb is only reached over HTTPS, so its proxy is preferred
+        let proxy = proxy(
+            clap_matches.remove_one::<String>("proxy"),
+            env::var("HTTPS_PROXY")
+                .or_else(|_| env::var("HTTP_PROXY"))
+                .ok(),
+        )?;
+
+        let batch = if batch_mode && cfg!(not(test)) {
+            search_terms_from_reader(stdin().lock())?
+        } else {
+            Vec::new()
+        };
+
+        let merge_ids =
+            if clap_matches.get_flag("merge-stdin") && cfg!(not(test)) {
+                use std::io::IsTerminal;
+                if !stdin().is_terminal() {
+                    imdb_ids_from_reader(stdin().lock())?
+                } else {
+                    log::warn!("--merge-stdin given but nothing was piped in");
+                    Vec::new()
+                }
+            } else {
+                Vec::new()
+            };
 
         Ok(RuntimeConfig {
             search_term,
@@ -185,8 +834,188 @@ impl RuntimeConfig {
             format,
             api_key,
             print_url,
+            merge_ids,
+            batch,
+            output,
+            append,
+            delimiter,
+            compact,
+            details,
+            fields,
+            no_save,
+            results_format,
+            resolve_years,
+            interactive_paginate,
+            max_requests,
+            always_tui,
+            benchmark: false,
+            user_agent,
+            timeout,
+            proxy,
+            refresh,
+            reconfigure,
+            dry_run,
+            ignore_quota,
+            open,
+            full_plot,
+            sort,
+            fuzzy,
+            exact,
+            clipboard,
+            favourite,
+            list_favourites,
+            pager,
+            verbosity,
+        })
+    }
+}
+
+// The flag takes priority over the environment variable, which takes priority
+// over the saved key (see main.rs). An empty environment variable is ignored
+fn api_key(flag: Option<String>, env: Option<String>) -> Option<String> {
+    flag.or_else(|| env.filter(|key| !key.trim().is_empty()))
+}
+
+// The flag takes priority over the environment variable
+fn user_agent(
+    flag: Option<String>,
+    env: Option<String>,
+) -> Result<Option<String>, ArgsError> {
+    match flag.or(env) {
+        Some(ua) if ua.trim().is_empty() => Err(ArgsError::EmptyUserAgent),
+        ua => Ok(ua),
+    }
+}
+
+// The flag takes priority over the environment variable. clap already makes
+// sure the flag is above 0
+fn timeout(flag: Option<u64>, env: Option<String>) -> Result<u64, ArgsError> {
+    match (flag, env) {
+        (Some(secs), _) => Ok(secs),
+        (None, Some(env)) => match env.trim().parse() {
+            Ok(secs) if secs > 0 => Ok(secs),
+            _ => Err(ArgsError::InvalidTimeout(env)),
+        },
+        (None, None) => Ok(DEFAULT_TIMEOUT_SECS),
+    }
+}
+
+// The flag takes priority over the environment variable. clap already makes
+// sure the flag is above 0. Unusable values of the environment variable are
+// ignored, as they always have been
+fn max_requests(flag: Option<usize>, env: Option<String>) -> usize {
+    flag.or_else(|| env?.trim().parse().ok().filter(|&n| n > 0))
+        .unwrap_or(DEFAULT_MAX_REQUESTS_PER_SEARCH)
+}
+
+// Names end up in a file name, so must be safe to use in one
+fn profile_name(name: &str) -> Result<String, ProfileNameError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    match !name.is_empty() && name.chars().all(allowed) {
+        true => Ok(name.to_owned()),
+        false => Err(ProfileNameError(name.to_owned())),
+    }
+}
+
+// Without -v, only warnings are shown, like before there was any logging
+fn verbosity(count: u8) -> LevelFilter {
+    match count {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
+
+// The flag takes priority over the environment variables, which are ignored
+// if they're set but empty
+fn proxy(
+    flag: Option<String>,
+    env: Option<String>,
+) -> Result<Option<Proxy>, ArgsError> {
+    match flag.or(env.filter(|env| !env.trim().is_empty())) {
+        Some(url) => match Proxy::new(url.trim()) {
+            Ok(proxy) => Ok(Some(proxy)),
+            Err(_) => Err(ArgsError::InvalidProxy(url)),
+        },
+        None => Ok(None),
+    }
+}
+
+// Used when the search term is piped in, e.g. `echo "up" | imdb-id`
+fn search_term_from_reader<R: Read>(
+    mut reader: R,
+) -> Result<String, ArgsError> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf).map_err(ArgsError::Stdin)?;
+    // Collapse newlines & repeated whitespace like with multiple word args
+    let search_term = buf.split_whitespace().join(" ");
+    if search_term.is_empty() {
+        Err(ArgsError::EmptyStdin)
+    } else {
+        Ok(search_term)
+    }
+}
+
+// Very short search terms almost always give "Too many results." from OMDb
+fn check_search_term_length(
+    search_term: String,
+    min_length: usize,
+) -> Result<String, ArgsError> {
+    if search_term.chars().count() < min_length {
+        Err(ArgsError::SearchTermTooShort(min_length))
+    } else {
+        Ok(search_term)
+    }
+}
+
+// Used by --batch, one title per line
+fn search_terms_from_reader<R: Read>(
+    mut reader: R,
+) -> Result<Vec<String>, ArgsError> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf).map_err(ArgsError::Stdin)?;
+    let search_terms = buf
+        .lines()
+        .map(|line| line.split_whitespace().join(" "))
+        .filter(|search_term| !search_term.is_empty())
+        .collect::<Vec<_>>();
+    if search_terms.is_empty() {
+        Err(ArgsError::EmptyStdin)
+    } else {
+        Ok(search_terms)
+    }
+}
+
+// Used by --merge-stdin. One ID per line, blank lines are skipped. Full URLs
+// (as given by --print-url) are accepted too
+fn imdb_ids_from_reader<R: Read>(
+    mut reader: R,
+) -> Result<Vec<String>, ArgsError> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf).map_err(ArgsError::Stdin)?;
+    let ids = buf
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix(crate::WEB_URL).unwrap_or(line)
         })
+        .filter(|id| !id.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+    Ok(ids)
+}
+
+// Used by --fields. Names are comma separated, repeats are ignored
+fn output_fields(names: &str) -> Result<Vec<output::OutputField>, ArgsError> {
+    let mut fields = Vec::new();
+    for name in names.split(',') {
+        let field = output::OutputField::from_name(name)
+            .ok_or_else(|| ArgsError::UnknownField(name.trim().to_owned()))?;
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
     }
+    Ok(fields)
 }
 
 impl Default for RuntimeConfig {
@@ -199,6 +1028,38 @@ impl Default for RuntimeConfig {
             format: OutputFormat::default(),
             api_key: None,
             print_url: false,
+            merge_ids: Vec::new(),
+            batch: Vec::new(),
+            output: None,
+            append: false,
+            delimiter: output::DEFAULT_DELIMITER,
+            compact: false,
+            details: false,
+            fields: None,
+            no_save: false,
+            results_format: None,
+            resolve_years: false,
+            interactive_paginate: false,
+            max_requests: DEFAULT_MAX_REQUESTS_PER_SEARCH,
+            always_tui: false,
+            benchmark: false,
+            user_agent: None,
+            timeout: DEFAULT_TIMEOUT_SECS,
+            proxy: None,
+            refresh: false,
+            reconfigure: false,
+            dry_run: false,
+            ignore_quota: false,
+            open: false,
+            full_plot: false,
+            sort: SortKey::default(),
+            fuzzy: false,
+            exact: false,
+            clipboard: false,
+            favourite: false,
+            list_favourites: false,
+            pager: Pager::default(),
+            verbosity: LevelFilter::Warn,
         }
     }
 }
@@ -209,25 +1070,126 @@ pub enum OutputFormat {
     #[default]
     Human,
     Json,
+    NdJson,
+    Tsv,
+    Csv,
+    Table,
+    Markdown,
     #[cfg(feature = "yaml")]
     Yaml,
+    #[cfg(feature = "html")]
+    Html,
+    #[cfg(feature = "toml")]
+    Toml,
 }
 
-impl FromStr for OutputFormat {
-    type Err = OutputFormatParseError;
+impl OutputFormat {
+    // Whether a second run's output can be added to the end of a file and
+    // still be valid
+    pub fn is_appendable(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Human | OutputFormat::NdJson | OutputFormat::Tsv
+        )
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use OutputFormat::*;
-        use OutputFormatParseError::*;
-        match s.to_ascii_lowercase().as_str() {
-            "human" | "plain" => Ok(Human),
-            "json" => Ok(Json),
+    // Whether whole entries (with lists and nested ratings) can be output,
+    // for --details
+    pub fn is_nestable(&self) -> bool {
+        match self {
+            OutputFormat::Json | OutputFormat::NdJson => true,
+            #[cfg(feature = "yaml")]
+            OutputFormat::Yaml => true,
+            _ => false,
+        }
+    }
+
+    // Whether the output is meant to be read by a person
+    pub fn is_pageable(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Human
+                | OutputFormat::Tsv
+                | OutputFormat::Csv
+                | OutputFormat::Table
+        )
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use OutputFormat::*;
+        match self {
+            Human => write!(f, "human"),
+            Json => write!(f, "json"),
+            NdJson => write!(f, "ndjson"),
+            Tsv => write!(f, "tsv"),
+            Csv => write!(f, "csv"),
+            Table => write!(f, "table"),
+            Markdown => write!(f, "markdown"),
+            #[cfg(feature = "yaml")]
+            Yaml => write!(f, "yaml"),
+            #[cfg(feature = "html")]
+            Html => write!(f, "html"),
+            #[cfg(feature = "toml")]
+            Toml => write!(f, "toml"),
+        }
+    }
+}
+
+// Serialized like MediaType, for the config's default_format
+impl Serialize for OutputFormat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputFormat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use OutputFormat::*;
+        use OutputFormatParseError::*;
+        match s.to_ascii_lowercase().as_str() {
+            "human" | "plain" => Ok(Human),
+            "json" => Ok(Json),
+            "ndjson" | "jsonl" => Ok(NdJson),
+            "tsv" => Ok(Tsv),
+            "csv" => Ok(Csv),
+            "table" => Ok(Table),
+            "markdown" | "md" => Ok(Markdown),
             #[cfg(feature = "yaml")]
             "yaml" => Ok(Yaml),
             #[cfg(not(feature = "yaml"))]
             not_installed @ "yaml" => {
                 Err(NotInstalled(not_installed.to_owned()))
             },
+            #[cfg(feature = "html")]
+            "html" => Ok(Html),
+            #[cfg(not(feature = "html"))]
+            not_installed @ "html" => {
+                Err(NotInstalled(not_installed.to_owned()))
+            },
+            #[cfg(feature = "toml")]
+            "toml" => Ok(Toml),
+            #[cfg(not(feature = "toml"))]
+            not_installed @ "toml" => {
+                Err(NotInstalled(not_installed.to_owned()))
+            },
             other => Err(Unrecognised(other.to_owned())),
         }
     }
@@ -286,7 +1248,7 @@ mod unit_tests {
             .unwrap();
         assert_eq!(m.get_one::<usize>("number_of_results"), Some(&3));
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
         assert_eq!(config.number_of_results, 3);
     }
 
@@ -296,225 +1258,932 @@ mod unit_tests {
         let mut m = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--results",
-                "7",
-                "foo",
+                "--results",
+                "7",
+                "foo",
+            ])
+            .unwrap();
+        assert_eq!(m.get_one::<usize>("number_of_results"), Some(&7));
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.number_of_results, 7);
+    }
+
+    #[test]
+    fn results_invalid() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--results",
+                "bar",
+                "foo",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn non_interactive_short() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-n", "foo"])
+            .unwrap();
+        assert!(m.get_flag("non-interactive"));
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(!config.interactive);
+        assert_eq!(config.number_of_results, 1);
+    }
+
+    #[test]
+    fn non_interactive_long() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--non-interactive",
+                "foo",
+            ])
+            .unwrap();
+        assert!(m.get_flag("non-interactive"));
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(!config.interactive);
+        assert_eq!(config.number_of_results, 1);
+    }
+
+    #[test]
+    fn verbosity_levels() {
+        let level = |args: &[&str]| {
+            let clap = RuntimeConfig::create_clap_app();
+            let mut m = clap
+                .try_get_matches_from(
+                    [env!("CARGO_PKG_NAME")].iter().chain(args).chain(&["up"]),
+                )
+                .unwrap();
+            RuntimeConfig::process_matches(&mut m, None)
+                .unwrap()
+                .verbosity
+        };
+        assert_eq!(level(&[]), LevelFilter::Warn);
+        assert_eq!(level(&["-v"]), LevelFilter::Info);
+        assert_eq!(level(&["--verbose"]), LevelFilter::Info);
+        assert_eq!(level(&["-vv"]), LevelFilter::Debug);
+        assert_eq!(level(&["-v", "-v", "-v"]), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn conflicting_r_n() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--non-interactive",
+                "--results",
+                "5",
+                "foo",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn require_search_term_if_n() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--non-interactive",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument)
+    }
+
+    #[test]
+    fn multiple_word_search_term() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "foo", "bar"])
+            .unwrap();
+        let search_term_word_count =
+            m.get_many::<String>("search_term").unwrap().count();
+        assert_eq!(search_term_word_count, 2);
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(&config.search_term, "foo bar");
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "up"])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(&config.search_term, "up");
+    }
+
+    #[test]
+    fn format_short() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-f", "json"])
+            .unwrap();
+        assert_eq!(
+            m.get_one::<OutputFormat>("format"),
+            Some(&OutputFormat::Json)
+        );
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.format, OutputFormat::Json);
+
+        #[cfg(feature = "yaml")]
+        {
+            let clap = RuntimeConfig::create_clap_app();
+            let mut m = clap
+                .try_get_matches_from(vec![
+                    env!("CARGO_PKG_NAME"),
+                    "-f",
+                    "yaml",
+                ])
+                .unwrap();
+            assert_eq!(
+                m.get_one::<OutputFormat>("format"),
+                Some(&OutputFormat::Yaml)
+            );
+
+            let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+            assert_eq!(config.format, OutputFormat::Yaml);
+        }
+    }
+
+    #[test]
+    fn format_long() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--format",
+                "json",
+            ])
+            .unwrap();
+        assert_eq!(
+            m.get_one::<OutputFormat>("format"),
+            Some(&OutputFormat::Json)
+        );
+
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.format, OutputFormat::Json);
+
+        #[cfg(feature = "yaml")]
+        {
+            let clap = RuntimeConfig::create_clap_app();
+            let mut m = clap
+                .try_get_matches_from(vec![
+                    env!("CARGO_PKG_NAME"),
+                    "--format",
+                    "yaml",
+                ])
+                .unwrap();
+            assert_eq!(
+                m.get_one::<OutputFormat>("format"),
+                Some(&OutputFormat::Yaml)
+            );
+
+            let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+            assert_eq!(config.format, OutputFormat::Yaml);
+        }
+    }
+
+    #[cfg(not(feature = "yaml"))]
+    #[test]
+    fn not_installed_format() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--format",
+                "yaml",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn format_ndjson() {
+        assert_eq!("ndjson".parse(), Ok(OutputFormat::NdJson));
+        assert_eq!("jsonl".parse(), Ok(OutputFormat::NdJson));
+        assert!(OutputFormat::NdJson.is_appendable());
+        assert!(!OutputFormat::NdJson.is_pageable());
+    }
+
+    #[test]
+    fn format_table() {
+        assert_eq!("table".parse(), Ok(OutputFormat::Table));
+        assert!(!OutputFormat::Table.is_appendable());
+        assert!(OutputFormat::Table.is_pageable());
+        assert_eq!("md".parse(), Ok(OutputFormat::Markdown));
+        assert!(!OutputFormat::Markdown.is_appendable());
+    }
+
+    #[test]
+    fn format_toml() {
+        let clap = RuntimeConfig::create_clap_app();
+        let m = clap.try_get_matches_from(vec![
+            env!("CARGO_PKG_NAME"),
+            "-f",
+            "toml",
+        ]);
+        #[cfg(feature = "toml")]
+        {
+            let mut m = m.unwrap();
+            let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+            assert_eq!(config.format, OutputFormat::Toml);
+        }
+        #[cfg(not(feature = "toml"))]
+        {
+            assert_eq!(m.unwrap_err().kind(), ErrorKind::ValueValidation);
+            assert!(matches!(
+                "toml".parse::<OutputFormat>(),
+                Err(OutputFormatParseError::NotInstalled(_)),
+            ));
+        }
+    }
+
+    #[cfg(not(feature = "html"))]
+    #[test]
+    fn not_installed_html() {
+        assert!(matches!(
+            "html".parse::<OutputFormat>(),
+            Err(OutputFormatParseError::NotInstalled(_)),
+        ));
+    }
+
+    #[test]
+    fn unrecognised_format() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--format",
+                "foo",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn append() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--output",
+                "ids.txt",
+                "--append",
+                "foo",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.output, Some(PathBuf::from("ids.txt")));
+        assert!(config.append);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "-o",
+                "ids.json",
+                "--append",
+                "-f",
+                "json",
+                "foo",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::NotAppendable(OutputFormat::Json));
+    }
+
+    #[test]
+    fn append_requires_output() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "--append"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn delimiter() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-f", "tsv"])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.format, OutputFormat::Tsv);
+        assert_eq!(config.delimiter, '\t');
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "-f",
+                "tsv",
+                "--delimiter",
+                "|",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.delimiter, '|');
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--delimiter",
+                ",",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::DelimiterWithoutTsv);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--compact",
+                "-f",
+                "ndjson",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::CompactWithoutJson);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--details",
+                "-f",
+                "csv",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::DetailsFormat(OutputFormat::Csv));
+    }
+
+    #[test]
+    fn fields() {
+        use output::OutputField::*;
+        assert_eq!(
+            output_fields("title,year,imdb_id").unwrap(),
+            vec![Title, Year, ImdbId],
+        );
+        assert_eq!(
+            output_fields(" ID, Type ,poster").unwrap(),
+            vec![ImdbId, MediaType, Poster],
+        );
+        assert_eq!(output_fields("year,year").unwrap(), vec![Year]);
+        assert_eq!(
+            output_fields("title,rating").unwrap_err(),
+            ArgsError::UnknownField("rating".into()),
+        );
+        assert_eq!(
+            output_fields("title,").unwrap_err(),
+            ArgsError::UnknownField("".into()),
+        );
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "-f",
+                "csv",
+                "--fields",
+                "imdb_id,title",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.fields, Some(vec![ImdbId, Title]));
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--fields",
+                "title",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::FieldsFormat(OutputFormat::Human));
+    }
+
+    #[test]
+    fn delimiter_invalid() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "-f",
+                "tsv",
+                "--delimiter",
+                "||",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn no_save() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "--no-save"])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(config.no_save);
+    }
+
+    #[test]
+    fn min_rating() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--min-rating",
+                "7.5",
+                "up",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.filters.min_rating, Some(7.5));
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--min-rating",
+                "11",
+                "up",
+            ])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::RatingOutOfRange(11.0));
+    }
+
+    #[test]
+    fn languages() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--language",
+                "French",
+                "--language",
+                " german ",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.filters.languages, ["French", "german"]);
+    }
+
+    #[test]
+    fn genres() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--genre",
+                "comedy",
+                "--genre",
+                "sci-fi",
+                "up",
             ])
             .unwrap();
-        assert_eq!(m.get_one::<usize>("number_of_results"), Some(&7));
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.filters.genres, [Genre::Comedy, Genre::SciFi]);
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert_eq!(config.number_of_results, 7);
-    }
-
-    #[test]
-    fn results_invalid() {
+        // Genres IMDb doesn't use are rejected
         let clap = RuntimeConfig::create_clap_app();
         let err = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--results",
-                "bar",
-                "foo",
+                "--genre",
+                "anime",
+                "up",
             ])
             .unwrap_err();
         assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("unknown genre \"anime\""));
     }
 
     #[test]
-    fn non_interactive_short() {
+    fn always_tui() {
         let clap = RuntimeConfig::create_clap_app();
         let mut m = clap
-            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-n", "foo"])
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "up"])
             .unwrap();
-        assert!(m.get_flag("non-interactive"));
-
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert!(!config.interactive);
-        assert_eq!(config.number_of_results, 1);
-    }
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(!config.use_tui(1));
+        assert!(config.use_tui(2));
 
-    #[test]
-    fn non_interactive_long() {
         let clap = RuntimeConfig::create_clap_app();
         let mut m = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--non-interactive",
-                "foo",
+                "--always-tui",
+                "up",
             ])
             .unwrap();
-        assert!(m.get_flag("non-interactive"));
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(config.use_tui(1));
+        assert!(config.use_tui(2));
+    }
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert!(!config.interactive);
-        assert_eq!(config.number_of_results, 1);
+    #[test]
+    fn timeout_sources() {
+        assert_eq!(timeout(None, None), Ok(DEFAULT_TIMEOUT_SECS));
+        assert_eq!(timeout(None, Some("5".into())), Ok(5));
+        assert_eq!(timeout(Some(10), Some("5".into())), Ok(10));
+        assert_eq!(
+            timeout(None, Some("0".into())),
+            Err(ArgsError::InvalidTimeout("0".into()))
+        );
+        assert_eq!(
+            timeout(None, Some("soon".into())),
+            Err(ArgsError::InvalidTimeout("soon".into()))
+        );
     }
 
     #[test]
-    fn conflicting_r_n() {
+    fn api_key_sources() {
+        let flag = || Some(String::from("abc123"));
+        let env = || Some(String::from("def456"));
+        assert_eq!(super::api_key(flag(), env()).as_deref(), Some("abc123"));
+        assert_eq!(super::api_key(None, env()).as_deref(), Some("def456"));
+        assert_eq!(super::api_key(None, Some(" ".into())), None);
+        assert_eq!(super::api_key(None, None), None);
+    }
+
+    #[test]
+    fn max_requests_sources() {
+        let default = DEFAULT_MAX_REQUESTS_PER_SEARCH;
+        assert_eq!(max_requests(None, None), default);
+        assert_eq!(max_requests(None, Some("5".into())), 5);
+        assert_eq!(max_requests(Some(20), Some("5".into())), 20);
+        assert_eq!(max_requests(None, Some("0".into())), default);
+        assert_eq!(max_requests(None, Some("lots".into())), default);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--max-requests",
+                "3",
+                "up",
+            ])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert_eq!(config.max_requests, 3);
+
         let clap = RuntimeConfig::create_clap_app();
         let err = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--non-interactive",
-                "--results",
-                "5",
-                "foo",
+                "--max-requests",
+                "0",
+                "up",
             ])
             .unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
     }
 
     #[test]
-    fn require_search_term_if_n() {
+    fn proxy_sources() {
+        let expected = Proxy::new("proxy.example.com:3128").unwrap();
+        assert_eq!(proxy(None, None), Ok(None));
+        assert_eq!(proxy(None, Some(" ".into())), Ok(None));
+        assert_eq!(
+            proxy(None, Some("http://proxy.example.com:3128".into())),
+            Ok(Some(expected.clone()))
+        );
+        assert_eq!(
+            proxy(Some("proxy.example.com:3128".into()), Some("env:1".into())),
+            Ok(Some(expected))
+        );
+        assert_eq!(
+            proxy(Some("socks5://proxy.example.com".into()), None),
+            Err(ArgsError::InvalidProxy("socks5://proxy.example.com".into()))
+        );
+        assert_eq!(
+            proxy(None, Some("proxy.example.com:http".into())),
+            Err(ArgsError::InvalidProxy("proxy.example.com:http".into()))
+        );
+    }
+
+    #[test]
+    fn user_agent_sources() {
+        assert_eq!(user_agent(None, None), Ok(None));
+        assert_eq!(
+            user_agent(None, Some("from-env".into())),
+            Ok(Some("from-env".into()))
+        );
+        assert_eq!(
+            user_agent(Some("from-flag".into()), Some("from-env".into())),
+            Ok(Some("from-flag".into()))
+        );
+        assert_eq!(
+            user_agent(None, Some(" ".into())),
+            Err(ArgsError::EmptyUserAgent)
+        );
+
         let clap = RuntimeConfig::create_clap_app();
         let err = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--non-interactive",
+                "--user-agent",
+                "",
             ])
             .unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument)
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
     }
 
     #[test]
-    fn multiple_word_search_term() {
+    fn profile() {
+        assert_eq!(profile_name("work"), Ok("work".into()));
+        assert_eq!(profile_name("my_profile-2"), Ok("my_profile-2".into()));
+        for bad in ["", "../work", "a b", "work.json"] {
+            assert_eq!(profile_name(bad), Err(ProfileNameError(bad.into())));
+        }
+
         let clap = RuntimeConfig::create_clap_app();
-        let mut m = clap
-            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "foo", "bar"])
+        let m = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--profile",
+                "work",
+                "up",
+            ])
             .unwrap();
-        let search_term_word_count =
-            m.get_many::<String>("search_term").unwrap().count();
-        assert_eq!(search_term_word_count, 2);
+        assert_eq!(m.get_one::<String>("profile").unwrap(), "work");
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert_eq!(&config.search_term, "foo bar");
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--profile",
+                "work",
+                "--config",
+                "/tmp/imdb-id.json",
+                "up",
+            ])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
     }
 
     #[test]
-    fn format_short() {
+    fn no_config() {
         let clap = RuntimeConfig::create_clap_app();
         let mut m = clap
-            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-f", "json"])
+            .try_get_matches_from(vec![
+                env!("CARGO_PKG_NAME"),
+                "--no-config",
+                "up",
+            ])
             .unwrap();
-        assert_eq!(
-            m.get_one::<OutputFormat>("format"),
-            Some(&OutputFormat::Json)
-        );
+        let disk_config = RuntimeConfig::disk_config(&m, || {
+            panic!("config shouldn't be loaded with --no-config")
+        });
+        assert!(disk_config.is_none());
+        let config =
+            RuntimeConfig::process_matches(&mut m, disk_config.as_ref())
+                .unwrap();
+        assert!(config.no_save);
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert_eq!(config.format, OutputFormat::Json);
+        // Loaded as normal without the flag
+        let clap = RuntimeConfig::create_clap_app();
+        let m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "up"])
+            .unwrap();
+        let disk_config =
+            RuntimeConfig::disk_config(&m, || Some(OnDiskConfig::default()));
+        assert!(disk_config.is_some());
+    }
 
-        #[cfg(feature = "yaml")]
-        {
+    #[test]
+    fn pager() {
+        let args = [
+            (vec!["--pager"], Pager::Always),
+            (vec!["--no-pager"], Pager::Never),
+            (vec![], Pager::Auto),
+            (vec!["--pager", "-f", "json"], Pager::Never),
+            (vec!["--pager", "--output", "ids.txt"], Pager::Never),
+        ];
+        for (args, expected) in args {
             let clap = RuntimeConfig::create_clap_app();
             let mut m = clap
-                .try_get_matches_from(vec![
-                    env!("CARGO_PKG_NAME"),
-                    "-f",
-                    "yaml",
-                ])
+                .try_get_matches_from(
+                    std::iter::once(env!("CARGO_PKG_NAME")).chain(args),
+                )
                 .unwrap();
-            assert_eq!(
-                m.get_one::<OutputFormat>("format"),
-                Some(&OutputFormat::Yaml)
-            );
-
-            let config = RuntimeConfig::process_matches(&mut m).unwrap();
-            assert_eq!(config.format, OutputFormat::Yaml);
+            let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+            assert_eq!(config.pager, expected);
         }
     }
 
     #[test]
-    fn format_long() {
+    fn api_key() {
         let clap = RuntimeConfig::create_clap_app();
         let mut m = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--format",
-                "json",
+                "--api-key",
+                "123483",
             ])
             .unwrap();
         assert_eq!(
-            m.get_one::<OutputFormat>("format"),
-            Some(&OutputFormat::Json)
+            m.remove_one::<String>("api_key").as_deref(),
+            Some("123483")
         );
+    }
 
-        let config = RuntimeConfig::process_matches(&mut m).unwrap();
-        assert_eq!(config.format, OutputFormat::Json);
+    #[test]
+    fn search_term_from_stdin() {
+        assert_eq!(
+            search_term_from_reader(&b"Kingsman\n"[..]).unwrap(),
+            "Kingsman",
+        );
+        assert_eq!(
+            search_term_from_reader(&b"  The Secret\nService \n"[..]).unwrap(),
+            "The Secret Service",
+        );
+    }
 
-        #[cfg(feature = "yaml")]
-        {
+    #[test]
+    fn empty_stdin() {
+        assert_eq!(
+            search_term_from_reader(&b""[..]).unwrap_err(),
+            ArgsError::EmptyStdin,
+        );
+        assert_eq!(
+            search_term_from_reader(&b" \n\t\n"[..]).unwrap_err(),
+            ArgsError::EmptyStdin,
+        );
+    }
+
+    #[test]
+    fn search_term_too_short() {
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "a"])
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::SearchTermTooShort(2));
+
+        let cfg = OnDiskConfig {
+            min_search_length: 4,
+            ..Default::default()
+        };
+        for (term, ok) in [("abc", false), ("abcd", true), ("abcde", true)] {
             let clap = RuntimeConfig::create_clap_app();
             let mut m = clap
-                .try_get_matches_from(vec![
-                    env!("CARGO_PKG_NAME"),
-                    "--format",
-                    "yaml",
-                ])
+                .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), term])
                 .unwrap();
-            assert_eq!(
-                m.get_one::<OutputFormat>("format"),
-                Some(&OutputFormat::Yaml)
-            );
+            let res = RuntimeConfig::process_matches(&mut m, Some(&cfg));
+            assert_eq!(res.is_ok(), ok, "{term:?} should be ok: {ok}");
+        }
+    }
 
-            let config = RuntimeConfig::process_matches(&mut m).unwrap();
-            assert_eq!(config.format, OutputFormat::Yaml);
+    #[test]
+    fn config_defaults() {
+        let cfg = OnDiskConfig {
+            default_media_type: Some(MediaType::SERIES),
+            default_format: Some(OutputFormat::Json),
+            default_results: Some(4),
+            ..Default::default()
+        };
+        let process = |args: &[&str], cfg: Option<&OnDiskConfig>| {
+            let clap = RuntimeConfig::create_clap_app();
+            let mut m = clap
+                .try_get_matches_from(
+                    [env!("CARGO_PKG_NAME")].iter().chain(args).chain(&["up"]),
+                )
+                .unwrap();
+            RuntimeConfig::process_matches(&mut m, cfg).unwrap()
+        };
+
+        // The config is used when there are no flags
+        let config = process(&[], Some(&cfg));
+        assert_eq!(config.filters.types, MediaType::SERIES);
+        assert_eq!(config.format, OutputFormat::Json);
+        assert_eq!(config.number_of_results, 4);
+
+        // Flags always win
+        let args = ["-t", "movie", "-f", "csv", "-r", "2"];
+        let config = process(&args, Some(&cfg));
+        assert_eq!(config.filters.types, MediaType::MOVIE);
+        assert_eq!(config.format, OutputFormat::Csv);
+        assert_eq!(config.number_of_results, 2);
+
+        // Without either, the built-in defaults are used
+        for cfg in [None, Some(&OnDiskConfig::default())] {
+            let config = process(&[], cfg);
+            assert_eq!(config.filters.types, MediaType::ALL);
+            assert_eq!(config.format, OutputFormat::Human);
+            assert_eq!(config.number_of_results, 10);
         }
     }
 
-    #[cfg(not(feature = "yaml"))]
     #[test]
-    fn not_installed_format() {
+    fn merge_stdin_ids() {
+        let piped = b"tt1049413\n\nhttps://www.imdb.com/title/tt8579674\n  tt2802144  \n";
+        assert_eq!(
+            imdb_ids_from_reader(&piped[..]).unwrap(),
+            ["tt1049413", "tt8579674", "tt2802144"],
+        );
+        assert!(imdb_ids_from_reader(&b""[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn batch_search_terms() {
+        let piped = b"Up\n\n  the   golden circle \n1917\n";
+        assert_eq!(
+            search_terms_from_reader(&piped[..]).unwrap(),
+            ["Up", "the golden circle", "1917"],
+        );
+        assert_eq!(
+            search_terms_from_reader(&b"\n \n"[..]).unwrap_err(),
+            ArgsError::EmptyStdin,
+        );
+
         let clap = RuntimeConfig::create_clap_app();
-        let err = clap
+        let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "--batch"])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(!config.interactive);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let mut m = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--format",
-                "yaml",
+                "--batch",
+                "-f",
+                "csv",
             ])
-            .unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+            .unwrap();
+        let err = RuntimeConfig::process_matches(&mut m, None).unwrap_err();
+        assert_eq!(err, ArgsError::BatchFormat(OutputFormat::Csv));
     }
 
     #[test]
-    fn unrecognised_format() {
+    fn favourites() {
         let clap = RuntimeConfig::create_clap_app();
-        let err = clap
+        let mut m = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--format",
-                "foo",
+                "--list-favourites",
+                "-f",
+                "json",
             ])
-            .unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::ValueValidation);
-    }
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(config.list_favourites);
+        assert!(!config.favourite);
 
-    #[test]
-    fn api_key() {
         let clap = RuntimeConfig::create_clap_app();
         let mut m = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "-b", "up"])
+            .unwrap();
+        let config = RuntimeConfig::process_matches(&mut m, None).unwrap();
+        assert!(config.favourite);
+
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
             .try_get_matches_from(vec![
                 env!("CARGO_PKG_NAME"),
-                "--api-key",
-                "123483",
+                "--list-favourites",
+                "up",
             ])
-            .unwrap();
-        assert_eq!(
-            m.remove_one::<String>("api_key").as_deref(),
-            Some("123483")
-        );
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn merge_stdin_requires_search_term() {
+        let clap = RuntimeConfig::create_clap_app();
+        let err = clap
+            .try_get_matches_from(vec![env!("CARGO_PKG_NAME"), "--merge-stdin"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
     }
 
     mod filters {
         use crate::filters::CURRENT_YEAR;
         use crate::omdb::MediaType;
-        use crate::{Filters, RuntimeConfig, Year};
+        use crate::{Filters, MediaTypeParseError, RuntimeConfig, Year};
+        use clap::error::ErrorKind;
         use clap::ArgMatches;
 
         fn from_matches(clap_matches: &mut ArgMatches) -> Filters {
-            RuntimeConfig::process_matches(clap_matches)
+            RuntimeConfig::process_matches(clap_matches, None)
                 .unwrap()
                 .filters
         }
@@ -534,7 +2203,8 @@ mod unit_tests {
                 filters,
                 Filters {
                     types: MediaType::SERIES,
-                    years: None,
+                    years: Vec::new(),
+                    ..Default::default()
                 }
             );
 
@@ -554,6 +2224,49 @@ mod unit_tests {
                     ..Default::default()
                 }
             );
+
+            // Separated by commas, and combined with another -t
+            let clap = RuntimeConfig::create_clap_app();
+            let mut clap_matches = clap
+                .try_get_matches_from(vec![
+                    env!("CARGO_PKG_NAME"),
+                    "-t",
+                    "movie,series",
+                ])
+                .unwrap();
+            let filters = from_matches(&mut clap_matches);
+            assert_eq!(filters.types, MediaType::MOVIE | MediaType::SERIES);
+            let clap = RuntimeConfig::create_clap_app();
+            let mut clap_matches = clap
+                .try_get_matches_from(vec![
+                    env!("CARGO_PKG_NAME"),
+                    "-t",
+                    "movie, series",
+                    "-t",
+                    "game",
+                ])
+                .unwrap();
+            let filters = from_matches(&mut clap_matches);
+            assert_eq!(filters.types, MediaType::ALL);
+
+            // Any unknown type is an error
+            assert_eq!(
+                "movie,bogus".parse::<MediaType>(),
+                Err(MediaTypeParseError("bogus".into())),
+            );
+            assert_eq!(
+                "movie,".parse::<MediaType>(),
+                Err(MediaTypeParseError("".into())),
+            );
+            let clap = RuntimeConfig::create_clap_app();
+            let err = clap
+                .try_get_matches_from(vec![
+                    env!("CARGO_PKG_NAME"),
+                    "-t",
+                    "movie,bogus",
+                ])
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueValidation);
         }
 
         #[test]
@@ -570,7 +2283,7 @@ mod unit_tests {
             assert_eq!(
                 filters,
                 Filters {
-                    years: Some(Year(1980..=1980)),
+                    years: vec![Year(1980..=1980)],
                     ..Default::default()
                 }
             );
@@ -587,7 +2300,7 @@ mod unit_tests {
             assert_eq!(
                 filters,
                 Filters {
-                    years: Some(Year(1980..=2010)),
+                    years: vec![Year(1980..=2010)],
                     ..Default::default()
                 }
             );
@@ -604,7 +2317,7 @@ mod unit_tests {
             assert_eq!(
                 filters,
                 Filters {
-                    years: Some(Year(1980..=*CURRENT_YEAR)),
+                    years: vec![Year(1980..=*CURRENT_YEAR)],
                     ..Default::default()
                 }
             );
@@ -621,7 +2334,7 @@ mod unit_tests {
             assert_eq!(
                 filters,
                 Filters {
-                    years: Some(Year(0..=2010)),
+                    years: vec![Year(0..=2010)],
                     ..Default::default()
                 }
             );
@@ -641,7 +2354,7 @@ mod unit_tests {
             assert_eq!(
                 filters,
                 Filters {
-                    years: Some(Year(1980..=2010)),
+                    years: vec![Year(1980..=2010)],
                     ..Default::default()
                 }
             );
@@ -664,7 +2377,8 @@ mod unit_tests {
                 filters,
                 Filters {
                     types: MediaType::MOVIE,
-                    years: Some(Year(1980..=2010)),
+                    years: vec![Year(1980..=2010)],
+                    ..Default::default()
                 }
             );
         }
@@ -1,3 +1,4 @@
+use crate::OutputFormat;
 use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::Display;
@@ -23,7 +24,7 @@ impl<E: MaybeFatal + Display> EmitNonFatal<E> for E {
         if self.is_fatal() {
             Err(self)
         } else {
-            eprintln!("WARNING: {self}");
+            log::warn!("{self}");
             Ok(())
         }
     }
@@ -32,7 +33,7 @@ impl<E: MaybeFatal + Display> EmitNonFatal<E> for E {
         if self.is_fatal() {
             panic!("emit_unconditional called on fatal error: {self}");
         } else {
-            eprintln!("WARNING: {self}");
+            log::warn!("{self}");
         }
     }
 }
@@ -45,7 +46,7 @@ impl<E: MaybeFatal + Display> EmitNonFatal<E> for Result<(), E> {
                 if e.is_fatal() {
                     Err(e)
                 } else {
-                    eprintln!("WARNING: {e}");
+                    log::warn!("{e}");
                     Ok(())
                 }
             },
@@ -57,7 +58,7 @@ impl<E: MaybeFatal + Display> EmitNonFatal<E> for Result<(), E> {
             if e.is_fatal() {
                 panic!("emit_unconditional called on fatal error: {e}");
             } else {
-                eprintln!("WARNING: {e}");
+                log::warn!("{e}");
             }
         }
     }
@@ -113,6 +114,18 @@ impl From<serde_json::Error> for FinalError {
     }
 }
 
+impl From<io::Error> for FinalError {
+    fn from(err: io::Error) -> Self {
+        FinalError::FormatOutput(Box::new(err))
+    }
+}
+
+impl From<csv::Error> for FinalError {
+    fn from(err: csv::Error) -> Self {
+        FinalError::FormatOutput(Box::new(err))
+    }
+}
+
 #[cfg(feature = "yaml")]
 impl From<serde_yaml::Error> for FinalError {
     fn from(err: serde_yaml::Error) -> Self {
@@ -120,6 +133,13 @@ impl From<serde_yaml::Error> for FinalError {
     }
 }
 
+#[cfg(feature = "toml")]
+impl From<toml::ser::Error> for FinalError {
+    fn from(err: toml::ser::Error) -> Self {
+        FinalError::FormatOutput(Box::new(err))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ArgsError {
     #[error("bad number of results: {0}")]
@@ -131,7 +151,43 @@ pub enum ArgsError {
     #[error(transparent)]
     MediaType(#[from] MediaTypeParseError),
     #[error(transparent)]
+    Genre(#[from] GenreParseError),
+    #[error(transparent)]
     SearchTerm(#[from] InteractivityError),
+    #[error("no search term provided on stdin")]
+    EmptyStdin,
+    #[error("--append can't be used with {0} output as it can't be added to")]
+    NotAppendable(OutputFormat),
+    #[error("--delimiter can only be used with tsv output")]
+    DelimiterWithoutTsv,
+    #[error("--compact can only be used with json output")]
+    CompactWithoutJson,
+    #[error("--batch can't be used with {0} output, only the default or json")]
+    BatchFormat(OutputFormat),
+    #[error(
+        "--details can't be used with {0} output, only json, ndjson, or yaml"
+    )]
+    DetailsFormat(OutputFormat),
+    #[error("--fields can't be used with {0} output")]
+    FieldsFormat(OutputFormat),
+    #[error("unknown field {0:?}, expected title, year, imdb_id, media_type, or poster")]
+    UnknownField(String),
+    #[error("the delimiter can't be a newline")]
+    NewlineDelimiter,
+    #[error("search term must be at least {0} characters long, try being more specific")]
+    SearchTermTooShort(usize),
+    #[error("failed to read search term from stdin: {0}")]
+    Stdin(io::Error),
+    #[error("the user agent can't be empty")]
+    EmptyUserAgent,
+    #[error(
+        "IMDB_ID_TIMEOUT must be a whole number of seconds above 0, not {0:?}"
+    )]
+    InvalidTimeout(String),
+    #[error("{0:?} isn't a usable proxy, it should look like [http://][user[:password]@]host[:port]")]
+    InvalidProxy(String),
+    #[error("minimum rating {0} isn't between 0 and 10")]
+    RatingOutOfRange(f32),
 }
 
 /*
@@ -150,6 +206,21 @@ impl PartialEq for ArgsError {
             (OutputFormat(a), OutputFormat(b)) => a == b,
             (MediaType(a), MediaType(b)) => a == b,
             (SearchTerm(_), SearchTerm(_)) => true,
+            (EmptyStdin, EmptyStdin) => true,
+            (SearchTermTooShort(a), SearchTermTooShort(b)) => a == b,
+            (NotAppendable(a), NotAppendable(b)) => a == b,
+            (DelimiterWithoutTsv, DelimiterWithoutTsv) => true,
+            (CompactWithoutJson, CompactWithoutJson) => true,
+            (BatchFormat(a), BatchFormat(b)) => a == b,
+            (DetailsFormat(a), DetailsFormat(b)) => a == b,
+            (FieldsFormat(a), FieldsFormat(b)) => a == b,
+            (UnknownField(a), UnknownField(b)) => a == b,
+            (NewlineDelimiter, NewlineDelimiter) => true,
+            (Stdin(_), Stdin(_)) => true,
+            (EmptyUserAgent, EmptyUserAgent) => true,
+            (InvalidTimeout(a), InvalidTimeout(b)) => a == b,
+            (InvalidProxy(a), InvalidProxy(b)) => a == b,
+            (RatingOutOfRange(a), RatingOutOfRange(b)) => a == b,
             _ => false,
         }
     }
@@ -173,6 +244,8 @@ pub enum YearParseError {
     NoYearsSpecified,
     #[error("start of date range is in the future")]
     StartInFuture,
+    #[error("{0}s isn't a decade, they start with a year ending in 0")]
+    NotADecade(u16),
 }
 
 #[derive(Debug, Error)]
@@ -180,6 +253,52 @@ pub enum YearParseError {
 #[error("unrecognised media type {0:?}")]
 pub struct MediaTypeParseError(pub String);
 
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[error(
+    "unknown genre {0:?}, expected one of: {}",
+    crate::omdb::Genre::known_names()
+)]
+pub struct GenreParseError(pub String);
+
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[error("unrecognised sort order {0:?}, expected relevance, year, year-desc, or title")]
+pub struct SortKeyParseError(pub String);
+
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[error(
+    "profile names can only have letters, numbers, - and _ in them, not {0:?}"
+)]
+pub struct ProfileNameError(pub String);
+
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+#[error("no wins or nominations found in awards {0:?}")]
+pub struct AwardsParseError(pub String);
+
+#[derive(Debug, Error)]
+pub enum RuntimeParseError {
+    #[error("run time {0:?} isn't in minutes")]
+    NoUnit(String),
+    #[error(transparent)]
+    InvalidInt(#[from] ParseIntError),
+}
+
+// Always printed "WARNING: {ListFormatParseError}", never fatal as the default
+// list format is used instead
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum ListFormatParseError {
+    #[error("unrecognised field {0:?} in tui.list_format, using the default")]
+    UnknownField(String),
+    #[error("unclosed {{ in tui.list_format, using the default")]
+    Unclosed,
+}
+
+impl MaybeFatal for ListFormatParseError {}
+
 #[derive(Debug, Error)]
 pub enum InteractivityError {
     #[error("user aborted operation")]
@@ -219,13 +338,77 @@ pub enum RequestError {
     #[error("Failed to parse response from OMDb, please raise an issue including the following text:\nSerde error: {0}\nJSON: \n```json\n{1}\n```")]
     Deserialisation(serde_json::Error, String),
     #[error("OMDb gave us an error: {0}")]
-    Omdb(String),
+    Omdb(OmdbError),
+    #[error("every request to OMDb failed, see the warnings above")]
+    AllRequestsFailed,
+    #[error("today's budget of {0} requests to OMDb has been used up (it can be changed with request_budget in the config, or ignored with --ignore-quota)")]
+    OverBudget(u32),
+    #[error("OMDb took too long to respond (the limit can be changed with --timeout)")]
+    Timeout,
+    #[error("unexpected response from OMDb, status {0}")]
+    Status(i32),
 }
 
 impl MaybeFatal for RequestError {
     fn is_fatal(&self) -> bool {
         use RequestError::*;
-        !matches!(self, Deserialisation(_, _))
+        match self {
+            Omdb(inner) => inner.is_fatal(),
+            _ => !matches!(self, Deserialisation(_, _) | Timeout | Status(_)),
+        }
+    }
+}
+
+// The errors OMDb is known to give, so they don't have to be told apart by
+// their messages
+#[derive(Debug, Error)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub enum OmdbError {
+    #[error("nothing was found")]
+    NotFound,
+    #[error("the daily request limit has been reached (backup keys can be added to backup_api_keys in the config)")]
+    RequestLimitReached,
+    #[error("too many results, try searching for more of the title")]
+    TooManyResults,
+    #[error(
+        "the API key isn't valid (a different one can be given with --api-key)"
+    )]
+    InvalidApiKey,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl OmdbError {
+    // What can be done differently, for errors caused by the search itself
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            OmdbError::TooManyResults => {
+                Some("Try adding -y or -t to narrow your search")
+            },
+            _ => None,
+        }
+    }
+}
+
+// Unknown errors stop everything, as they always have
+impl MaybeFatal for OmdbError {
+    fn is_fatal(&self) -> bool {
+        !matches!(self, OmdbError::NotFound | OmdbError::TooManyResults)
+    }
+}
+
+impl From<String> for OmdbError {
+    fn from(message: String) -> Self {
+        use OmdbError::*;
+        match message.as_str() {
+            // Movie/Series/Episode/Series or season not found!
+            m if m.ends_with("not found!") => NotFound,
+            "Incorrect IMDb ID." => NotFound,
+            "Request limit reached!" => RequestLimitReached,
+            "Too many results." => TooManyResults,
+            "Invalid API key!" | "No API key provided." => InvalidApiKey,
+            _ => Other(message),
+        }
     }
 }
 
@@ -254,8 +437,8 @@ impl MaybeFatal for SignUpError {
 pub enum ApiKeyError {
     #[error("invalid API key format")]
     InvalidFormat,
-    #[error("issue with web request: {0}")]
-    RequestFailed(#[from] minreq::Error),
+    #[error(transparent)]
+    RequestFailed(RequestError),
     #[error("unauthorised API key")]
     Unauthorised,
     #[error("unexpected response to API key, status {0}")]
@@ -285,6 +468,44 @@ pub enum DiskError {
     Write(io::Error),
     #[error("failed to convert config to JSON for writing: {0}")]
     Serialise(serde_json::Error),
+    #[error("failed to use the keyring: {0}")]
+    Keyring(String),
+    #[error("failed to use the cache of fetched entries: {0}")]
+    Cache(io::Error),
+    #[error("failed to read your favourites: {0}")]
+    FavouritesRead(io::Error),
+    #[error("failed to interpret your favourites at {1}: {0}")]
+    FavouritesDeserialise(#[source] serde_json::Error, Cow<'static, str>), // path (converted lossy)
+    #[error("failed to save your favourites: {0}")]
+    FavouritesWrite(io::Error),
+    #[error("failed to read how many requests have been made today: {0}")]
+    RequestCountRead(io::Error),
+    #[error("failed to interpret how many requests have been made today at {1}: {0}")]
+    RequestCountDeserialise(#[source] serde_json::Error, Cow<'static, str>), // path (converted lossy)
+    #[error("failed to save how many requests have been made today: {0}")]
+    RequestCountWrite(io::Error),
+}
+
+// Copying is an extra, so these are never fatal
+#[derive(Debug, Error)]
+#[error("couldn't copy to the clipboard: {0}")]
+pub struct ClipboardError(#[from] arboard::Error);
+
+impl MaybeFatal for ClipboardError {}
+
+impl DiskError {
+    // Extra advice to print after the warning, if there's anything the user
+    // can do about it
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            DiskError::Write(_) => Some(
+                "Your API key will only be used for this session. Fix the \
+                permissions of the config file, or use --no-save to stop \
+                trying to save it",
+            ),
+            _ => None,
+        }
+    }
 }
 
 impl MaybeFatal for DiskError {}
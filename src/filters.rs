@@ -1,9 +1,10 @@
-use crate::omdb::{MediaType, SearchResult};
+use crate::omdb::{Genre, MediaType, SearchResult};
 use crate::YearParseError;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::fmt;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
@@ -21,40 +22,104 @@ pub(crate) static CURRENT_YEAR: Lazy<u16> = Lazy::new(|| {
         .expect("Bad current year")
 });
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Filters {
     pub types: MediaType,
-    pub years: Option<Year>,
+    // Media only needs to be in one of the ranges. Empty allows any year
+    pub years: Vec<Year>,
+    // Only known once each result's entry has been fetched, see allows_rating
+    pub min_rating: Option<f32>,
+    // Also only known from the entry, see allows_genres
+    pub genres: Vec<Genre>,
+    // Also only known from the entry, see allows_languages
+    pub languages: Vec<String>,
 }
 
 impl Filters {
     pub fn allows(&self, search_result: &SearchResult) -> bool {
-        let year_matches = self
-            .years
-            .as_ref()
-            .map(|year| year.contains(&search_result.year))
-            .unwrap_or(true);
+        let year_matches = self.years.is_empty()
+            || self
+                .years
+                .iter()
+                .any(|year| year.contains(&search_result.year));
         let media_type_matches = self.types.contains(search_result.media_type);
         year_matches && media_type_matches
     }
 
+    // Unrated media is never allowed if there's a minimum rating
+    pub fn allows_rating(&self, rating: Option<f32>) -> bool {
+        match (self.min_rating, rating) {
+            (None, _) => true,
+            (Some(min_rating), Some(rating)) => rating >= min_rating,
+            (Some(_), None) => false,
+        }
+    }
+
+    // Media only needs one of the genres to be allowed. Media with no genres
+    // is never allowed if any are given
+    pub fn allows_genres(&self, genres: Option<&[String]>) -> bool {
+        if self.genres.is_empty() {
+            return true;
+        }
+        genres.unwrap_or_default().iter().any(|genre| {
+            self.genres.iter().any(|wanted| wanted == genre.as_str())
+        })
+    }
+
+    // Media only needs one of the languages to be allowed, ignoring case.
+    // Media with no languages is never allowed if any are given
+    pub fn allows_languages(&self, languages: Option<&[String]>) -> bool {
+        if self.languages.is_empty() {
+            return true;
+        }
+        languages.unwrap_or_default().iter().any(|language| {
+            self.languages
+                .iter()
+                .any(|wanted| wanted.eq_ignore_ascii_case(language))
+        })
+    }
+
+    // Whether results need their entries fetching to be filtered
+    pub fn needs_entries(&self) -> bool {
+        self.min_rating.is_some()
+            || !self.genres.is_empty()
+            || !self.languages.is_empty()
+    }
+
     pub fn combinations(&self) -> usize {
         let types = if self.types.is_all() {
             1
         } else {
             self.types.count()
         };
-        let years = self.years.as_ref().map(|year| year.0.len()).unwrap_or(1);
+        let years = match self.search_years().len() {
+            0 => 1,
+            years => years,
+        };
         types * years
     }
+
+    // Each year that needs searching, oldest first. Overlapping ranges only
+    // give each year once
+    pub fn search_years(&self) -> Vec<u16> {
+        self.years
+            .iter()
+            .flat_map(|year| year.0.clone())
+            .sorted_unstable()
+            .dedup()
+            .collect()
+    }
 }
 
 impl Default for Filters {
     fn default() -> Self {
         Filters {
             types: MediaType::ALL,
-            years: None,
+            years: Vec::new(),
+            min_rating: None,
+            genres: Vec::new(),
+            languages: Vec::new(),
         }
     }
 }
@@ -75,6 +140,43 @@ impl Year {
     fn is_single(&self) -> bool {
         self.0.start() == self.0.end()
     }
+
+    // Open-ended ranges like "2016–" end in the current year (see Limitation
+    // above), so this also includes ranges that genuinely end this year
+    pub fn is_open_ended(&self) -> bool {
+        !self.is_single() && *self.0.end() == *CURRENT_YEAR
+    }
+}
+
+impl Year {
+    // Comma separated years/ranges, e.g. "1990-1995,2000,2010-"
+    pub fn parse_list(list: &str) -> Result<Vec<Year>, YearParseError> {
+        list.split(',')
+            .map(|year_str| match year_str.trim() {
+                "" => Err(YearParseError::NoYearsSpecified),
+                year_str => Year::from_str(year_str),
+            })
+            .collect()
+    }
+
+    // The first and last years of a year or a decade (like 1990s). The
+    // current decade ends this year
+    fn years_of(year_str: &str) -> Result<(u16, u16), YearParseError> {
+        match year_str.strip_suffix(['s', 'S']) {
+            Some(decade) if decade.len() == 4 => {
+                let start = u16::from_str(decade)?;
+                if start % 10 != 0 {
+                    return Err(YearParseError::NotADecade(start));
+                }
+                let end = (start + 9).min(max(start, *CURRENT_YEAR));
+                Ok((start, end))
+            },
+            _ => {
+                let year = u16::from_str(year_str)?;
+                Ok((year, year))
+            },
+        }
+    }
 }
 
 impl FromStr for Year {
@@ -84,10 +186,15 @@ impl FromStr for Year {
     fn from_str(year_str: &str) -> Result<Self, Self::Err> {
         use YearParseError::*;
 
-        match year_str.split_once(&Year::SEPARATORS[..]) {
+        // A trailing + is the same as a trailing separator, e.g. 2000+
+        let range = match year_str.strip_suffix('+') {
+            Some(start_str) => Some((start_str, "")),
+            None => year_str.split_once(&Year::SEPARATORS[..]),
+        };
+        match range {
             Some((start_str, end_str)) => {
                 let mut start = if !start_str.is_empty() {
-                    let start = u16::from_str(start_str)?;
+                    let (start, _) = Year::years_of(start_str)?;
                     // Make sure start isn't in the future
                     if start > *CURRENT_YEAR {
                         return Err(StartInFuture);
@@ -98,10 +205,12 @@ impl FromStr for Year {
                 };
 
                 let mut end = if !end_str.is_empty() {
-                    let mut end = u16::from_str(end_str)?;
+                    let (_, mut end) = Year::years_of(end_str)?;
                     // Make sure arg isn't bigger than current year
                     if end > *CURRENT_YEAR {
-                        eprintln!("WARNING: using current year for end of date range instead");
+                        log::warn!(
+                            "using current year for end of date range instead"
+                        );
                         end = *CURRENT_YEAR;
                     }
                     end
@@ -113,16 +222,18 @@ impl FromStr for Year {
 
                 // Save the user from their silliness
                 if end < start {
-                    eprintln!("WARNING: looks like you put the date range in backwards, fixed that for you");
+                    log::warn!("looks like you put the date range in backwards, fixed that for you");
                     std::mem::swap(&mut start, &mut end);
                 }
 
                 Ok(Year(start..=end))
             },
             None => {
-                // Should be just a year we can parse
-                let year = min(u16::from_str(year_str)?, *CURRENT_YEAR);
-                Ok(Year(year..=year))
+                // Should be just a year (or decade) we can parse
+                let (start, end) = Year::years_of(year_str)?;
+                let start = min(start, *CURRENT_YEAR);
+                let end = min(end, *CURRENT_YEAR);
+                Ok(Year(start..=end))
             },
         }
     }
@@ -156,7 +267,8 @@ impl fmt::Display for Year {
         } else {
             let start = *self.0.start();
             let end = *self.0.end();
-            if start != 0 {
+            // Leaving out both ends would just be "-", which doesn't parse
+            if start != 0 || end == *CURRENT_YEAR {
                 write!(f, "{start}")?;
             }
             write!(f, "-")?;
@@ -170,24 +282,26 @@ impl fmt::Display for Year {
 
 #[cfg(test)]
 mod filters_unit_tests {
-    use crate::omdb::MediaType;
+    use crate::omdb::{Genre, MediaType};
     use crate::{Filters, Year};
 
     #[test]
     fn combinations() {
-        let filters = vec![
+        let filters = [
             Filters::default(),
             Filters {
-                years: Some(Year(1960..=1970)),
+                years: vec![Year(1960..=1970)],
                 ..Default::default()
             },
             Filters {
                 types: MediaType::SERIES,
-                years: Some(Year(1985..=2000)),
+                years: vec![Year(1985..=2000)],
+                ..Default::default()
             },
             Filters {
                 types: MediaType::MOVIE,
-                years: Some(Year(1980..=2000)),
+                years: vec![Year(1980..=2000)],
+                ..Default::default()
             },
         ];
         let expected: Vec<usize> = vec![1, 11, 16, 21];
@@ -205,6 +319,112 @@ mod filters_unit_tests {
             });
     }
 
+    #[test]
+    fn combinations_multiple_ranges() {
+        let filters = Filters {
+            years: vec![Year(1990..=1995), Year(2000..=2000)],
+            ..Default::default()
+        };
+        assert_eq!(filters.combinations(), 7);
+
+        // Overlapping years are only searched once
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: vec![Year(1990..=1995), Year(1994..=1996)],
+            ..Default::default()
+        };
+        assert_eq!(filters.search_years(), (1990..=1996).collect::<Vec<_>>());
+        assert_eq!(filters.combinations(), 14);
+    }
+
+    #[test]
+    fn combinations_multiple_types() {
+        // Two types isn't ALL, so each type needs its own request per year
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: vec![Year(1990..=2000)],
+            ..Default::default()
+        };
+        assert_eq!(filters.combinations(), 22);
+
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::GAME,
+            years: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(filters.combinations(), 2);
+    }
+
+    #[test]
+    fn min_rating() {
+        let unfiltered = Filters::default();
+        assert!(unfiltered.allows_rating(Some(2.0)));
+        assert!(unfiltered.allows_rating(None));
+
+        let filters = Filters {
+            min_rating: Some(7.5),
+            ..Default::default()
+        };
+        assert!(filters.allows_rating(Some(8.2)));
+        assert!(filters.allows_rating(Some(7.5)));
+        assert!(!filters.allows_rating(Some(7.3)));
+        assert!(!filters.allows_rating(None));
+    }
+
+    #[test]
+    fn genres() {
+        let up = ["Animation", "Adventure", "Comedy"].map(String::from);
+        let unfiltered = Filters::default();
+        assert!(unfiltered.allows_genres(Some(&up)));
+        assert!(unfiltered.allows_genres(None));
+
+        let filters = Filters {
+            genres: vec![Genre::Comedy, Genre::Horror],
+            ..Default::default()
+        };
+        assert!(filters.needs_entries());
+        assert!(filters.allows_genres(Some(&up)));
+        assert!(filters.allows_genres(Some(&["comedy".into()])));
+        assert!(!filters.allows_genres(Some(&["Drama".into()])));
+        assert!(!filters.allows_genres(None));
+
+        // Genres that imdb-id doesn't know about still work
+        let filters = Filters {
+            genres: vec![Genre::Other("Anime".into())],
+            ..Default::default()
+        };
+        assert!(filters.allows_genres(Some(&["ANIME".into()])));
+        assert!(!filters.allows_genres(Some(&up)));
+    }
+
+    #[test]
+    fn languages() {
+        let up = ["English".to_owned()];
+        let nineteen_seventeen =
+            ["English", "French", "German"].map(String::from);
+        let unfiltered = Filters::default();
+        assert!(unfiltered.allows_languages(Some(&up)));
+        assert!(unfiltered.allows_languages(None));
+
+        let filters = Filters {
+            languages: vec!["french".into()],
+            ..Default::default()
+        };
+        assert!(filters.needs_entries());
+        assert!(filters.allows_languages(Some(&nineteen_seventeen)));
+        assert!(!filters.allows_languages(Some(&up)));
+        assert!(!filters.allows_languages(None));
+
+        // Any of the languages given will do
+        let filters = Filters {
+            languages: vec!["Japanese".into(), "ENGLISH".into()],
+            ..Default::default()
+        };
+        assert!(filters.allows_languages(Some(&up)));
+        assert!(filters.allows_languages(Some(&nineteen_seventeen)));
+        assert!(!filters.allows_languages(Some(&["Korean".into()])));
+    }
+
     mod filtering {
         use crate::omdb::{MediaType, SearchResult};
         use crate::{Filters, Year};
@@ -220,36 +440,42 @@ mod filters_unit_tests {
                         imdb_id: "tt4649466".into(),
                         media_type: MediaType::MOVIE,
                         year: Year(2017..=2017),
+                        poster: None,
                     },
                     SearchResult {
                         title: "King's Man".into(),
                         imdb_id: "tt1582211".into(),
                         media_type: MediaType::MOVIE,
                         year: Year(2010..=2010),
+                        poster: None,
                     },
                     SearchResult {
                         title: "All the King's Men".into(),
                         imdb_id: "tt0405676".into(),
                         media_type: MediaType::MOVIE,
                         year: Year(2006..=2006),
+                        poster: None,
                     },
                     SearchResult {
                         title: "All the King's Men".into(),
                         imdb_id: "tt0041113".into(),
                         media_type: MediaType::MOVIE,
                         year: Year(1949..=1949),
+                        poster: None,
                     },
                     SearchResult {
                         title: "Black Mirror".into(),
                         imdb_id: "tt2085059".into(),
                         media_type: MediaType::SERIES,
                         year: Year(2016..=2021),
+                        poster: None,
                     },
                     SearchResult {
                         title: "Seinfeld".into(),
                         imdb_id: "tt0098904".into(),
                         media_type: MediaType::SERIES,
                         year: Year(1989..=1998),
+                        poster: None,
                     },
                 ]
             });
@@ -275,14 +501,16 @@ mod filters_unit_tests {
         fn media_type_single() {
             let test = Filters {
                 types: MediaType::MOVIE,
-                years: None,
+                years: Vec::new(),
+                ..Default::default()
             };
             let results = [true, true, true, true, false, false];
             assert_eq!(&get_outcomes(&test), &results);
 
             let test = Filters {
                 types: MediaType::SERIES,
-                years: None,
+                years: Vec::new(),
+                ..Default::default()
             };
             let results = [false, false, false, false, true, true];
             assert_eq!(&get_outcomes(&test), &results);
@@ -291,14 +519,14 @@ mod filters_unit_tests {
         #[test]
         fn years() {
             let test = Filters {
-                years: Some(Year(2020..=2021)),
+                years: vec![Year(2020..=2021)],
                 ..Default::default()
             };
             let results = [false, false, false, false, true, false];
             assert_eq!(&get_outcomes(&test), &results);
 
             let test = Filters {
-                years: Some(Year(1950..=2010)),
+                years: vec![Year(1950..=2010)],
                 ..Default::default()
             };
             let results = [false, true, true, false, false, true];
@@ -309,14 +537,16 @@ mod filters_unit_tests {
         fn mixed() {
             let test = Filters {
                 types: MediaType::MOVIE,
-                years: Some(Year(1950..=2010)),
+                years: vec![Year(1950..=2010)],
+                ..Default::default()
             };
             let results = [false, true, true, false, false, false];
             assert_eq!(&get_outcomes(&test), &results);
 
             let test = Filters {
                 types: MediaType::SERIES,
-                years: Some(Year(2010..=2021)),
+                years: vec![Year(2010..=2021)],
+                ..Default::default()
             };
             let results = [false, false, false, false, true, false];
             assert_eq!(&get_outcomes(&test), &results);
@@ -328,6 +558,7 @@ mod filters_unit_tests {
 mod year_unit_tests {
     use super::Year;
     use super::CURRENT_YEAR;
+    use crate::YearParseError;
     use once_cell::sync::Lazy;
     use std::ops::RangeInclusive;
     use std::str::FromStr;
@@ -366,4 +597,83 @@ mod year_unit_tests {
     fn from_str_invalid() {
         Year::from_str("-").unwrap_err();
     }
+
+    #[test]
+    fn decades() {
+        assert_eq!(Year::from_str("1990s").unwrap().0, 1990..=1999);
+        assert_eq!(Year::from_str("2000s").unwrap().0, 2000..=2009);
+        let this_decade = *CURRENT_YEAR - *CURRENT_YEAR % 10;
+        assert_eq!(
+            Year::from_str(&format!("{this_decade}s")).unwrap().0,
+            this_decade..=*CURRENT_YEAR,
+        );
+        // Future decades are clamped like future years
+        let next_decade = this_decade + 10;
+        assert_eq!(
+            Year::from_str(&format!("{next_decade}s")).unwrap().0,
+            *CURRENT_YEAR..=*CURRENT_YEAR,
+        );
+
+        // In ranges, from the start of one decade to the end of the other
+        assert_eq!(Year::from_str("1990s-2000s").unwrap().0, 1990..=2009);
+        assert_eq!(Year::from_str("1995-2000s").unwrap().0, 1995..=2009);
+        assert_eq!(Year::from_str("-1980s").unwrap().0, 0..=1989);
+        assert_eq!(Year::from_str("1980s-").unwrap().0, 1980..=*CURRENT_YEAR);
+
+        assert_eq!(
+            Year::from_str("1995s"),
+            Err(YearParseError::NotADecade(1995)),
+        );
+        assert!(Year::from_str("90s").is_err());
+        assert!(Year::from_str("s").is_err());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let ranges = [
+            0..=0,
+            0..=1999,
+            0..=*CURRENT_YEAR,
+            1..=*CURRENT_YEAR,
+            1999..=1999,
+            1999..=*CURRENT_YEAR,
+            1920..=1925,
+            *CURRENT_YEAR..=*CURRENT_YEAR,
+            *CURRENT_YEAR - 1..=*CURRENT_YEAR,
+        ];
+        for range in ranges {
+            let year = Year(range);
+            let parsed = Year::from_str(&year.to_string());
+            assert_eq!(parsed, Ok(year.clone()), "{year} didn't round trip");
+        }
+        // Every year there's been
+        assert_eq!(Year(0..=*CURRENT_YEAR).to_string(), "0-");
+    }
+
+    #[test]
+    fn plus_onwards() {
+        assert_eq!(Year::from_str("2000+").unwrap().0, 2000..=*CURRENT_YEAR);
+        assert_eq!(Year::from_str("1990s+").unwrap().0, 1990..=*CURRENT_YEAR);
+        // The same as a trailing separator, which still works
+        assert_eq!(Year::from_str("2000+"), Year::from_str("2000-"));
+        assert_eq!(Year::from_str("-2000").unwrap().0, 0..=2000);
+        assert_eq!(Year::from_str("+"), Err(YearParseError::NoYearsSpecified),);
+        assert!(Year::from_str("2000+2010").is_err());
+    }
+
+    #[test]
+    fn parse_list() {
+        let years = Year::parse_list("1990-1995,2000, 2010-").unwrap();
+        let ranges = years.into_iter().map(|year| year.0).collect::<Vec<_>>();
+        assert_eq!(ranges, [1990..=1995, 2000..=2000, 2010..=*CURRENT_YEAR]);
+
+        // A single year or range is still fine
+        let years = Year::parse_list("1920-1925").unwrap();
+        assert_eq!(years[0].0, 1920..=1925);
+        assert_eq!(years.len(), 1);
+
+        assert!(Year::parse_list("1990,,2000").is_err());
+        assert!(Year::parse_list("1990,").is_err());
+        assert!(Year::parse_list("1990,soon").is_err());
+    }
 }
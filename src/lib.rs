@@ -0,0 +1,29 @@
+//! imdb-id looks up the IMDb ID of a movie or show, given its name. Besides
+//! the binary, searches and lookups can be done through [`omdb::Client`]
+//!
+//! Everything other than `omdb` (and the types it uses, re-exported here) is
+//! only public for the binary's sake, and isn't covered by semver
+
+#[doc(hidden)]
+pub mod clap_wrap;
+mod errors;
+mod filters;
+#[doc(hidden)]
+pub mod logging;
+pub mod omdb;
+#[doc(hidden)]
+pub mod output;
+mod persistent;
+#[doc(hidden)]
+pub mod user_input;
+
+#[doc(hidden)]
+pub use clap_wrap::*;
+pub use errors::*;
+pub use filters::*;
+#[doc(hidden)]
+pub use persistent::*;
+
+// prefix to be used by print-url option
+#[doc(hidden)]
+pub const WEB_URL: &str = "https://www.imdb.com/title/";
@@ -0,0 +1,33 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+// Everything logged goes to stderr, like the rest of the messages that aren't
+// output. Warnings look the same as they always have
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match record.level() {
+            Level::Warn => eprintln!("WARNING: {}", record.args()),
+            level => eprintln!("{level}: {}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+// Should be called before anything else, so no warnings are missed. Only
+// warnings (and worse) are shown until the level is changed with -v
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Warn);
+    }
+}
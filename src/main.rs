@@ -1,25 +1,18 @@
-mod clap_wrap;
-mod errors;
-mod filters;
-pub mod omdb;
-mod persistent;
-mod user_input;
-
-pub use clap_wrap::*;
-pub use errors::*;
-pub use filters::*;
-pub use persistent::*;
-
-use clap_wrap::OutputFormat::*;
-use omdb::{test_api_key, RequestBundle, SearchResult};
+use imdb_id::OutputFormat::*;
+use imdb_id::*;
+use omdb::{
+    batch_search, filter_by_entry, get_details, is_imdb_id, keep_exact_titles,
+    merge_results, rank_by_similarity, resolve_years, sort_results,
+    test_api_key, ApiKeys, Client, Details, Matches, Quota, RequestSettings,
+    SearchResult,
+};
+use serde::Serialize;
 use std::borrow::Cow;
 use std::cmp::min;
+use std::io::Write;
 use std::process;
 use user_input::cli::get_api_key;
 
-// prefix to be used by print-url option
-const WEB_URL: &str = "https://www.imdb.com/title/";
-
 fn main() {
     if let Err(why) = app() {
         if why.is_fatal() {
@@ -30,68 +23,354 @@ fn main() {
 }
 
 fn app() -> Result<(), FinalError> {
-    let runtime_config = RuntimeConfig::new()?;
-    let disk_config = match OnDiskConfig::load() {
-        Ok(cfg) => Some(cfg),
-        Err(e) => {
-            // Suppress not found errors
-            if !matches!(e, DiskError::NotFound(_)) {
-                e.emit_unconditional();
-            }
-            None
-        },
-    };
+    logging::init();
+    let (runtime_config, mut disk_config) =
+        RuntimeConfig::new(|| match OnDiskConfig::load() {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                // Suppress not found errors
+                if !matches!(e, DiskError::NotFound(_)) {
+                    e.emit_unconditional();
+                }
+                None
+            },
+        })?;
+
+    log::set_max_level(runtime_config.verbosity);
+
+    let cache_days = disk_config
+        .as_ref()
+        .map(|cfg| cfg.cache_days)
+        .unwrap_or(OnDiskConfig::DEFAULT_CACHE_DAYS);
+    let request_budget = disk_config
+        .as_ref()
+        .map(|cfg| cfg.request_budget)
+        .unwrap_or(OnDiskConfig::DEFAULT_REQUEST_BUDGET);
+    omdb::set_request_settings(RequestSettings {
+        user_agent: runtime_config.user_agent.clone(),
+        timeout_secs: Some(runtime_config.timeout),
+        proxy: runtime_config.proxy.clone(),
+        entry_cache: (cache_days > 0)
+            .then(|| EntryCache::new(cache_days, runtime_config.refresh)),
+        // Only used once the main API key has reached its limit
+        api_keys: disk_config
+            .as_ref()
+            .map(|cfg| ApiKeys::new(cfg.backup_api_keys.clone())),
+        quota: Some(Quota::new(request_budget, !runtime_config.ignore_quota)),
+    });
+
+    if runtime_config.benchmark {
+        omdb::benchmark();
+        return Ok(());
+    }
+
+    // Nothing is sent, so there's no need for an API key
+    if runtime_config.dry_run {
+        let bundle = omdb::RequestBundle::new(
+            "",
+            &runtime_config.search_term,
+            &runtime_config.filters,
+            runtime_config.max_requests,
+        );
+        for url in bundle.dry_run() {
+            println!("{url}");
+        }
+        return Ok(());
+    }
+
+    let save_failures = SaveFailures::new();
+    if let Some(cfg) = disk_config.as_mut() {
+        if cfg.migrate() && !runtime_config.no_save {
+            save_config(cfg, &save_failures);
+        }
+    }
+
+    // Favourites are saved locally, so there's no need for an API key
+    if runtime_config.list_favourites {
+        let favourites = Favourites::load().unwrap_or_else(|why| {
+            why.emit_unconditional();
+            Favourites::default()
+        });
+        let results = favourites
+            .0
+            .into_iter()
+            .map(SearchResult::from)
+            .collect::<Vec<_>>();
+        let mut output = output::Output::open(
+            runtime_config.output.as_deref(),
+            runtime_config.append,
+            runtime_config.pager,
+        )?;
+        match runtime_config.format {
+            Human => {
+                let lines =
+                    output::favourites(&results, runtime_config.print_url);
+                write!(output, "{lines}")?;
+            },
+            _ => write_formatted(&mut output, &results, None, &runtime_config)?,
+        }
+        output.finish()?;
+        return Ok(());
+    }
 
     // Get API key into one place, regardless as to where it's provided
     let api_key: Option<Cow<str>> =
         match (&runtime_config.api_key, &disk_config) {
-            // Prefer CLI arg
+            // Prefer CLI arg, then IMDB_ID_API_KEY (see clap_wrap)
             (Some(s), _) => Some(Cow::Borrowed(s.as_str())),
-            (None, Some(OnDiskConfig { api_key })) => {
+            (None, Some(OnDiskConfig { api_key, .. })) => {
                 Some(Cow::Borrowed(api_key))
             },
             (None, None) => None,
         };
 
-    // Check/Get API key
+    // Check/Get API key. The saved key doesn't need checking if OMDb
+    // accepted it recently
+    let now = unix_time();
+    let recently_validated = |api_key: &str| {
+        !runtime_config.reconfigure
+            && disk_config.as_ref().is_some_and(|cfg| {
+                cfg.api_key == api_key && cfg.recently_validated(now)
+            })
+    };
+    let mut last_validated =
+        disk_config.as_ref().and_then(|cfg| cfg.last_validated);
     let api_key = match api_key {
+        Some(api_key) if recently_validated(&api_key) => {
+            log::debug!("Not checking the API key, it was checked recently");
+            api_key
+        },
         Some(api_key) => match test_api_key(&api_key) {
-            Ok(()) => api_key,
+            Ok(()) => {
+                last_validated = Some(now);
+                api_key
+            },
             Err(e) => {
                 e.emit_non_fatal()?;
+                last_validated = Some(now);
                 get_api_key()?.into()
             },
         },
-        None => get_api_key()?.into(),
+        None => {
+            last_validated = Some(now);
+            get_api_key()?.into()
+        },
     };
     // API key should now always be a good one
 
-    // Update/Save API key to disk if needed
-    match &disk_config {
-        Some(cfg) if cfg.api_key != api_key => {
+    // Update/Save API key (and when it was checked) to disk if needed
+    let config_changed = disk_config
+        .as_ref()
+        .map(|cfg| {
+            cfg.api_key != api_key || cfg.last_validated != last_validated
+        })
+        .unwrap_or(true);
+    if config_changed && !runtime_config.no_save {
+        let new_config = OnDiskConfig {
+            api_key: api_key.clone(),
+            last_validated,
+            ..disk_config.clone().unwrap_or_default()
+        };
+        save_config(&new_config, &save_failures);
+    }
+    let mut tui_config = disk_config
+        .as_ref()
+        .map(|cfg| cfg.tui.clone())
+        .unwrap_or_default();
+    if runtime_config.results_format.is_some() {
+        tui_config.list_format = runtime_config.results_format.clone();
+    }
+
+    if !runtime_config.batch.is_empty() {
+        let batch_results = batch_search(
+            &api_key,
+            &runtime_config.batch,
+            &runtime_config.filters,
+            runtime_config.max_requests,
+        )?;
+        let mut output = output::Output::open(
+            runtime_config.output.as_deref(),
+            runtime_config.append,
+            runtime_config.pager,
+        )?;
+        match runtime_config.format {
+            Json => {
+                let json =
+                    output::json(&batch_results, runtime_config.compact)?;
+                writeln!(output, "{json}")?;
+            },
+            _ => {
+                let lines =
+                    output::batch(&batch_results, runtime_config.print_url);
+                write!(output, "{lines}")?;
+            },
+        }
+        output.finish()?;
+        return Ok(());
+    }
+
+    // Okay let's actually do the search
+    let client = Client::new(api_key.as_ref())
+        .with_max_requests(runtime_config.max_requests);
+    let spinner =
+        output::Spinner::new(runtime_config.interactive, runtime_config.format);
+    let show_progress = |made, most| {
+        if let Some(spinner) = &spinner {
+            spinner.requests_made(made, most);
+        }
+    };
+    // Only set if the search has to be narrowed down, see below
+    let narrowed;
+    let mut search_bundle = client
+        .bundle(&runtime_config.search_term, &runtime_config.filters)
+        .with_progress(&show_progress);
+    let allow_reading_time = matches!(runtime_config.format, Human);
+    let search = || {
+        search_bundle
+            .get_results(runtime_config.number_of_results, allow_reading_time)
+    };
+    // No need to search if we've been given an ID, just check it exists
+    let matches = if is_imdb_id(&runtime_config.search_term) {
+        match client.entry(&runtime_config.search_term) {
+            Ok(entry) => Ok(Matches {
+                results: vec![SearchResult::from(entry)],
+                total: 1,
+            }),
+            Err(RequestError::Omdb(
+                why @ (OmdbError::NotFound | OmdbError::Other(_)),
+            )) => {
+                log::warn!(
+                    "{} isn't a known IMDb ID ({why}), searching \
+                    for it instead",
+                    runtime_config.search_term,
+                );
+                search()
+            },
+            Err(why) => Err(why),
+        }
+    } else {
+        search()
+    };
+    // OMDb didn't accept the key after all, so it's checked next time
+    if let Err(RequestError::Omdb(OmdbError::InvalidApiKey)) = &matches {
+        if !runtime_config.no_save {
             let new_config = OnDiskConfig {
                 api_key: api_key.clone(),
+                last_validated: None,
+                ..disk_config.clone().unwrap_or_default()
             };
-            new_config.save().emit_unconditional();
-        },
-        None => {
-            let new_config = OnDiskConfig {
-                api_key: api_key.clone(),
+            save_config(&new_config, &save_failures);
+        }
+    }
+    // OMDb won't give any results for a search that's too broad, so it can be
+    // narrowed down to a year instead of having to start again
+    let matches = match matches {
+        Err(RequestError::Omdb(err @ OmdbError::TooManyResults)) => {
+            if let Some(spinner) = &spinner {
+                spinner.clear();
+            }
+            if let Some(suggestion) = err.suggestion() {
+                eprintln!("{suggestion}");
+            }
+            let year = match runtime_config.interactive {
+                true => user_input::cli::get_year_filter()?,
+                false => None,
             };
-            new_config.save().emit_unconditional();
+            match year {
+                Some(year) => {
+                    narrowed = Filters {
+                        years: vec![year],
+                        ..runtime_config.filters.clone()
+                    };
+                    search_bundle =
+                        client.bundle(&runtime_config.search_term, &narrowed);
+                    search_bundle.get_results(
+                        runtime_config.number_of_results,
+                        allow_reading_time,
+                    )?
+                },
+                None => return Err(RequestError::Omdb(err).into()),
+            }
         },
-        // API key is same on disk as is being used
-        _ => {},
+        matches => matches?,
+    };
+    if let Some(spinner) = &spinner {
+        spinner.clear();
     }
-
-    // Okay let's actually do the search
-    let search_bundle = RequestBundle::new(
-        &api_key,
-        &runtime_config.search_term,
-        &runtime_config.filters,
+    let total = matches.total;
+    log::info!(
+        "OMDb gave {} results, out of {total} matches",
+        matches.results.len(),
     );
-    let allow_reading_time = matches!(runtime_config.format, Human);
-    let search_results = search_bundle.get_results(allow_reading_time)?;
+    let mut search_results = matches.results;
+    // An ID given as the search term is never the title of what it's for
+    if runtime_config.exact && !is_imdb_id(&runtime_config.search_term) {
+        search_results =
+            keep_exact_titles(search_results, &runtime_config.search_term)?;
+    }
+
+    if !search_results.is_empty() && !runtime_config.no_save {
+        let mut new_config = OnDiskConfig {
+            api_key: api_key.clone(),
+            last_validated,
+            ..disk_config.clone().unwrap_or_default()
+        };
+        if new_config.remember_search(&runtime_config.search_term) {
+            // Not being able to save this shouldn't stop the search
+            save_config(&new_config, &save_failures);
+        }
+    }
+
+    if runtime_config.fuzzy {
+        rank_by_similarity(&mut search_results, &runtime_config.search_term);
+    }
+
+    if !runtime_config.merge_ids.is_empty() {
+        let piped = runtime_config
+            .merge_ids
+            .iter()
+            .filter_map(|imdb_id| match client.entry(imdb_id) {
+                Ok(entry) => Some(SearchResult::from(entry)),
+                Err(why) => {
+                    log::warn!("skipping piped ID {imdb_id}: {why}");
+                    None
+                },
+            })
+            .collect();
+        search_results = merge_results(piped, search_results);
+    }
+
+    sort_results(&mut search_results, runtime_config.sort);
+
+    if runtime_config.filters.needs_entries() {
+        eprintln!(
+            "Checking the details of each result, this takes a request per \
+            result"
+        );
+        search_results = filter_by_entry(
+            &api_key,
+            search_results,
+            &runtime_config.filters,
+            runtime_config.number_of_results,
+        );
+        log::info!(
+            "{} results left after checking their details",
+            search_results.len(),
+        );
+    }
+
+    if runtime_config.resolve_years {
+        // Only bother with the results that will be shown
+        let end_index =
+            min(runtime_config.number_of_results, search_results.len());
+        resolve_years(&api_key, &mut search_results[..end_index], end_index);
+    }
+
+    let mut output = output::Output::open(
+        runtime_config.output.as_deref(),
+        runtime_config.append,
+        runtime_config.pager,
+    )?;
 
     match runtime_config.format {
         Human => {
@@ -99,52 +378,263 @@ fn app() -> Result<(), FinalError> {
                 return Err(FinalError::Interaction(
                     InteractivityError::Cancel,
                 ));
-            } else if !runtime_config.interactive || search_results.len() == 1 {
+            } else if !runtime_config.use_tui(search_results.len()) {
                 let search_result = &search_results[0];
                 if runtime_config.interactive {
                     eprintln!("Only one result; {search_result}");
                 }
-                if runtime_config.print_url {
-                    print!("{}", WEB_URL); // Not println! so there's no newline
+                print_choice(
+                    &mut output,
+                    &search_result.imdb_id,
+                    &runtime_config,
+                )?;
+                if runtime_config.favourite {
+                    add_favourite(search_result);
                 }
-                println!("{}", search_result.imdb_id);
             } else {
                 // Guaranteed to be interactive
                 use crate::user_input::tui::TuiOutcome::*;
                 let end_index =
                     min(search_results.len(), runtime_config.number_of_results);
-                match user_input::tui(&api_key, &search_results[..end_index])? {
+                // Lets people know when narrowing the search would help
+                if total as usize > end_index {
+                    eprintln!("Showing {end_index} of {total} results");
+                }
+                match user_input::tui(
+                    &api_key,
+                    search_results,
+                    end_index,
+                    &search_bundle,
+                    runtime_config.interactive_paginate,
+                    runtime_config.full_plot,
+                    &tui_config,
+                )? {
                     Picked(sr) => {
-                        if runtime_config.print_url {
-                            print!("{}", WEB_URL); // Not println! so there's no newline
+                        print_choice(
+                            &mut output,
+                            &sr.imdb_id,
+                            &runtime_config,
+                        )?;
+                        if runtime_config.favourite {
+                            add_favourite(&sr);
                         }
-                        println!("{}", sr.imdb_id);
                     },
                     PickedError(sr, err) => {
                         eprintln!("{err}\n");
-                        if runtime_config.print_url {
-                            print!("{}", WEB_URL); // Not println! so there's no newline
+                        print_choice(
+                            &mut output,
+                            &sr.imdb_id,
+                            &runtime_config,
+                        )?;
+                        if runtime_config.favourite {
+                            add_favourite(&sr);
+                        }
+                    },
+                    PickedEpisode(episode) => {
+                        print_choice(
+                            &mut output,
+                            &episode.imdb_id,
+                            &runtime_config,
+                        )?;
+                        if runtime_config.favourite {
+                            log::warn!(
+                                "episodes can't be added to your \
+                                favourites, only movies and series"
+                            );
                         }
-                        println!("{}", sr.imdb_id);
                     },
                     Quit => {},
                 }
             }
         },
-        Json => {
+        _ if runtime_config.details => {
             let end_index =
                 min(runtime_config.number_of_results, search_results.len());
-            let json =
-                serde_json::to_string_pretty(&search_results[..end_index])?;
-            println!("{json}");
+            eprintln!(
+                "Getting the details of each result, this takes a request per \
+                result"
+            );
+            let details = get_details(&api_key, &search_results[..end_index]);
+            write_details(&mut output, &details, total, &runtime_config)?;
         },
-        #[cfg(feature = "yaml")]
-        Yaml => {
+        _ => {
             let end_index =
                 min(runtime_config.number_of_results, search_results.len());
-            let yaml = serde_yaml::to_string(&search_results[..end_index])?;
-            println!("{yaml}");
+            write_formatted(
+                &mut output,
+                &search_results[..end_index],
+                Some(total),
+                &runtime_config,
+            )?;
+        },
+    }
+    output.finish()?;
+    Ok(())
+}
+
+// Like write_formatted, but for the formats that --details supports
+fn write_details(
+    output: &mut output::Output,
+    details: &[Details],
+    total: u32,
+    runtime_config: &RuntimeConfig,
+) -> Result<(), FinalError> {
+    match runtime_config.format {
+        Json => write_json(output, details, Some(total), runtime_config)?,
+        NdJson => output::ndjson(details, output)?,
+        #[cfg(feature = "yaml")]
+        Yaml => {
+            let yaml = serde_yaml::to_string(details)?;
+            writeln!(output, "{yaml}")?;
+        },
+        _ => unreachable!("--details is only allowed with nestable formats"),
+    }
+    Ok(())
+}
+
+// Writes the results in one of the machine-readable --formats
+fn write_formatted(
+    output: &mut output::Output,
+    results: &[SearchResult],
+    total: Option<u32>,
+    runtime_config: &RuntimeConfig,
+) -> Result<(), FinalError> {
+    let fields = runtime_config.fields.as_deref();
+    let columns = fields.unwrap_or(&output::DEFAULT_FIELDS);
+    match (runtime_config.format, fields) {
+        (Human, _) => unreachable!("human output isn't machine-readable"),
+        (Json, None) => write_json(output, results, total, runtime_config)?,
+        (Json, Some(fields)) => {
+            let selected = output::select(results, fields);
+            write_json(output, &selected, total, runtime_config)?;
+        },
+        (NdJson, None) => output::ndjson(results, output)?,
+        (NdJson, Some(fields)) => {
+            output::ndjson(&output::select(results, fields), output)?
+        },
+        (Tsv, _) => {
+            let delimiter = runtime_config.delimiter;
+            let tsv = output::delimited(results, delimiter, columns);
+            write!(output, "{tsv}")?;
+        },
+        (Csv, _) => output::csv(results, columns, output)?,
+        (Table, _) => {
+            let table = output::table(results, columns, output.width());
+            write!(output, "{table}")?;
+        },
+        (Markdown, _) => {
+            let markdown = output::markdown(results, columns);
+            write!(output, "{markdown}")?;
         },
+        #[cfg(feature = "yaml")]
+        (Yaml, None) => {
+            let yaml = serde_yaml::to_string(results)?;
+            writeln!(output, "{yaml}")?;
+        },
+        #[cfg(feature = "yaml")]
+        (Yaml, Some(fields)) => {
+            let yaml = serde_yaml::to_string(&output::select(results, fields))?;
+            writeln!(output, "{yaml}")?;
+        },
+        #[cfg(feature = "html")]
+        (Html, _) => {
+            let html = output::html(results);
+            write!(output, "{html}")?;
+        },
+        #[cfg(feature = "toml")]
+        (Toml, None) => {
+            let toml = output::toml(results)?;
+            write!(output, "{toml}")?;
+        },
+        #[cfg(feature = "toml")]
+        (Toml, Some(fields)) => {
+            let toml = output::toml(&output::select(results, fields))?;
+            write!(output, "{toml}")?;
+        },
+    }
+    Ok(())
+}
+
+// Every save of the config goes through here, so a config that can't be saved
+// is only warned about once a run. What to do about it is only suggested once
+// saving has failed on an earlier run too
+fn save_config(config: &OnDiskConfig, failures: &SaveFailures) {
+    let why = match config.save() {
+        Ok(()) => {
+            failures.record(false);
+            return;
+        },
+        Err(why) => why,
+    };
+    if let Some(failed_before) = failures.record(true) {
+        let hint = why.hint().filter(|_| failed_before);
+        why.emit_unconditional();
+        if let Some(hint) = hint {
+            log::warn!("{hint}");
+        }
+    }
+}
+
+// Searches give their total alongside the results, anything else (e.g. the
+// list of favourites) is just the results
+fn write_json<T: Serialize + ?Sized>(
+    output: &mut output::Output,
+    results: &T,
+    total: Option<u32>,
+    runtime_config: &RuntimeConfig,
+) -> Result<(), FinalError> {
+    let compact = runtime_config.compact;
+    let json = match total {
+        Some(total_results) => output::json(
+            &output::WithTotal {
+                total_results,
+                results,
+            },
+            compact,
+        )?,
+        None => output::json(results, compact)?,
+    };
+    writeln!(output, "{json}")?;
+    Ok(())
+}
+
+// --favourite isn't worth failing over, so any problems are only warned about
+fn add_favourite(search_result: &SearchResult) {
+    match Favourites::add(search_result) {
+        Ok(true) => eprintln!("Added {search_result} to your favourites"),
+        Ok(false) => {
+            eprintln!("{search_result} is already one of your favourites")
+        },
+        Err(why) => why.emit_unconditional(),
+    }
+}
+
+// Prints the ID (or URL) of the result (or episode) the user went with, then
+// copies and/or opens it if they asked for that
+fn print_choice(
+    output: &mut output::Output,
+    imdb_id: &str,
+    runtime_config: &RuntimeConfig,
+) -> Result<(), FinalError> {
+    if runtime_config.print_url {
+        write!(output, "{}", WEB_URL)?; // Not writeln! so there's no newline
+    }
+    writeln!(output, "{imdb_id}")?;
+    if runtime_config.clipboard {
+        let text = if runtime_config.print_url {
+            Cow::Owned(format!("{WEB_URL}{imdb_id}"))
+        } else {
+            Cow::Borrowed(imdb_id)
+        };
+        output::copy_to_clipboard(&text).emit_unconditional();
+    }
+    if runtime_config.open {
+        let url = format!("{WEB_URL}{imdb_id}/");
+        if let Err(why) = opener::open_browser(&url) {
+            log::warn!(
+                "couldn't open your browser ({why}), please visit {url}"
+            );
+        }
     }
     Ok(())
 }
@@ -1,31 +1,149 @@
 use crate::{
-    ApiKeyError, Filters, MaybeFatal, MediaTypeParseError, RequestError, Year,
+    ApiKeyError, AwardsParseError, EmitNonFatal, EntryCache, Filters,
+    GenreParseError, MaybeFatal, MediaTypeParseError, OmdbError, RequestCount,
+    RequestError, RuntimeParseError, SortKeyParseError, Year,
 };
 use bitflags::bitflags;
 use itertools::Itertools;
-use minreq::Request;
-use once_cell::sync::Lazy;
+use lazy_regex::{lazy_regex, Regex};
+use minreq::{Proxy, Request};
+use once_cell::sync::{Lazy, OnceCell};
 use serde::de::{DeserializeOwned, Error};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::{smallvec, SmallVec};
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::fmt::{self, Debug};
 use std::str::FromStr;
-use std::time::Duration;
-use std::{env, thread};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::{env, io, thread};
+use strsim::normalized_levenshtein;
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::Date;
 
-const DEFAULT_MAX_REQUESTS_PER_SEARCH: usize = 10;
+pub const DEFAULT_MAX_REQUESTS_PER_SEARCH: usize = 10;
+// For requests that don't depend on each other, like checking ratings
+const CONCURRENT_REQUESTS: usize = 4;
 
-static MAX_REQUESTS_PER_SEARCH: Lazy<usize> =
-    Lazy::new(|| match env::var("IMDB_ID_MAX_REQUESTS_PER_SEARCH") {
-        Ok(str) => str.parse().unwrap_or(DEFAULT_MAX_REQUESTS_PER_SEARCH),
-        Err(_) => DEFAULT_MAX_REQUESTS_PER_SEARCH,
-    });
+static IMDB_ID_REGEX: Lazy<Regex> = lazy_regex!(r"^tt\d{7,8}$");
+
+static OSCARS_REGEX: Lazy<Regex> = lazy_regex!(r"\bWon (\d+) Oscars?\b");
+static WINS_REGEX: Lazy<Regex> = lazy_regex!(r"\b(\d+) wins?\b");
+static NOMINATIONS_REGEX: Lazy<Regex> = lazy_regex!(r"\b(\d+) nominations?\b");
+
+const DEFAULT_USER_AGENT: &str = concat!("imdb-id/", env!("CARGO_PKG_VERSION"));
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// How every request is made. Anything not given is left at its default, or
+/// not used at all (e.g. there's no proxy unless one is given)
+#[derive(Debug, Default)]
+pub struct RequestSettings {
+    pub user_agent: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub proxy: Option<Proxy>,
+    pub entry_cache: Option<EntryCache>,
+    pub api_keys: Option<ApiKeys>,
+    pub quota: Option<Quota>,
+}
+
+static REQUEST_SETTINGS: OnceCell<RequestSettings> = OnceCell::new();
+
+static DEFAULT_REQUEST_SETTINGS: RequestSettings = RequestSettings {
+    user_agent: None,
+    timeout_secs: None,
+    proxy: None,
+    entry_cache: None,
+    api_keys: None,
+    quota: None,
+};
+
+/// Should be called before any requests are made. Only the first settings
+/// given are used, for the rest of the run
+pub fn set_request_settings(settings: RequestSettings) {
+    let _ = REQUEST_SETTINGS.set(settings);
+}
+
+fn request_settings() -> &'static RequestSettings {
+    REQUEST_SETTINGS.get().unwrap_or(&DEFAULT_REQUEST_SETTINGS)
+}
+
+/// Sends requests to OMDb, giving back the body of the response. Everything
+/// sent to OMDb goes through one of these, so tests can give canned responses
+/// instead of using the network
+pub trait OmdbClient: Sync {
+    fn get(&self, request: Request) -> Result<String, RequestError>;
+}
+
+/// The real thing, using minreq
+#[derive(Debug, Copy, Clone)]
+pub struct Minreq;
+
+impl OmdbClient for Minreq {
+    fn get(&self, request: Request) -> Result<String, RequestError> {
+        if let Some(quota) = &request_settings().quota {
+            quota.take()?;
+        }
+        let response = request.send().map_err(|why| match why {
+            // A read timing out gives WouldBlock on some platforms
+            minreq::Error::IoError(io)
+                if matches!(
+                    io.kind(),
+                    io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                ) =>
+            {
+                RequestError::Timeout
+            },
+            other => RequestError::Web(other),
+        })?;
+        // OMDb's own errors come with one of these, anything else is a problem
+        // with getting to OMDb
+        match response.status_code {
+            200 | 401 => Ok(response.as_str()?.to_owned()),
+            status => Err(RequestError::Status(status)),
+        }
+    }
+}
+
+// So closures can stand in for a client
+impl<F> OmdbClient for F
+where
+    F: Fn(Request) -> Result<String, RequestError> + Sync,
+{
+    fn get(&self, request: Request) -> Result<String, RequestError> {
+        self(request)
+    }
+}
+
+// All requests should be made through this, so they have the User-Agent,
+// timeout, and proxy
+pub(crate) fn get(url: impl Into<minreq::URL>) -> Request {
+    get_via(url, request_settings().proxy.as_ref())
+}
+
+fn get_via(url: impl Into<minreq::URL>, proxy: Option<&Proxy>) -> Request {
+    let settings = request_settings();
+    let user_agent =
+        settings.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+    let timeout = settings.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let request = minreq::get(url)
+        .with_header("User-Agent", user_agent)
+        .with_timeout(timeout);
+    match proxy {
+        Some(proxy) => request.with_proxy(proxy.clone()),
+        None => request,
+    }
+}
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum OmdbResult<T> {
-    Err(OmdbError),
+    Err(ErrorResponse),
     Ok(T),
 }
 
@@ -33,14 +151,147 @@ impl<T> From<OmdbResult<T>> for Result<T, RequestError> {
     fn from(omdb_result: OmdbResult<T>) -> Self {
         match omdb_result {
             OmdbResult::Ok(t) => Ok(t),
-            OmdbResult::Err(e) => Err(RequestError::Omdb(e.error)),
+            OmdbResult::Err(e) => Err(RequestError::Omdb(e.error.into())),
+        }
+    }
+}
+
+/// A daily budget of requests, counted across runs, so that OMDb's own limit
+/// is never reached. Every request sent by Minreq is counted against it
+#[derive(Debug)]
+pub struct Quota {
+    count: Mutex<RequestCount>,
+    budget: u32,
+    // With --ignore-quota requests are still counted, but never refused
+    enforced: bool,
+    // Only false in tests, so they don't touch the real count
+    save: bool,
+}
+
+impl Quota {
+    // Warned about once this much of the budget has been used
+    const WARN_AT: f64 = 0.9;
+
+    pub fn new(budget: u32, enforced: bool) -> Self {
+        let count = RequestCount::load().unwrap_or_else(|why| {
+            why.emit_unconditional();
+            RequestCount::default()
+        });
+        Quota {
+            count: Mutex::new(count),
+            budget,
+            enforced,
+            save: true,
+        }
+    }
+
+    fn take(&self) -> Result<(), RequestError> {
+        self.take_on(&crate::today())
+    }
+
+    // Counts a request made on the date given, unless it would go over the
+    // budget. A budget of 0 is no budget at all
+    fn take_on(&self, date: &str) -> Result<(), RequestError> {
+        let mut count = self.count.lock().expect("Request count poisoned");
+        let limited = self.enforced && self.budget > 0;
+        if limited && count.on(date) >= self.budget {
+            return Err(RequestError::OverBudget(self.budget));
+        }
+        let made = count.add(date);
+        if limited && made == (self.budget as f64 * Quota::WARN_AT) as u32 {
+            log::warn!(
+                "{made} of today's {} requests to OMDb have been made",
+                self.budget,
+            );
+        }
+        if self.save {
+            if let Err(why) = count.save() {
+                why.emit_unconditional();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Backup API keys, used once OMDb says a key has reached its daily limit.
+/// Keys that have reached their limit are skipped for the rest of the run
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    backups: Vec<String>,
+    exhausted: Mutex<HashSet<String>>,
+}
+
+impl ApiKeys {
+    pub fn new(backups: Vec<String>) -> Self {
+        ApiKeys {
+            backups,
+            exhausted: Mutex::default(),
+        }
+    }
+
+    // The key itself if it's still usable, otherwise the first backup that is
+    fn usable(&self, key: &str) -> Option<String> {
+        let exhausted = self.exhausted.lock().expect("API keys poisoned");
+        std::iter::once(key)
+            .chain(self.backups.iter().map(String::as_str))
+            .find(|key| !exhausted.contains(*key))
+            .map(ToOwned::to_owned)
+    }
+
+    // Marks the key as having reached its limit, giving the one to use instead
+    fn exhaust(&self, key: &str) -> Option<String> {
+        let newly_exhausted = self
+            .exhausted
+            .lock()
+            .expect("API keys poisoned")
+            .insert(key.to_owned());
+        let next = self.usable(key);
+        if newly_exhausted && next.is_some() {
+            log::warn!(
+                "one of your API keys has reached OMDb's daily \
+                limit, using the next one"
+            );
+        }
+        next
+    }
+}
+
+// Without any backups, the key given is the only one
+fn usable_key(
+    keys: Option<&ApiKeys>,
+    api_key: &str,
+) -> Result<String, RequestError> {
+    match keys {
+        Some(keys) => keys
+            .usable(api_key)
+            .ok_or(RequestError::Omdb(OmdbError::RequestLimitReached)),
+        None => Ok(api_key.to_owned()),
+    }
+}
+
+// Makes the request with each usable key in turn, until one of them isn't
+// over OMDb's daily limit
+fn with_failover<T>(
+    keys: Option<&ApiKeys>,
+    api_key: &str,
+    mut attempt: impl FnMut(&str) -> Result<T, RequestError>,
+) -> Result<T, RequestError> {
+    let mut key = usable_key(keys, api_key)?;
+    loop {
+        match attempt(&key) {
+            Err(RequestError::Omdb(OmdbError::RequestLimitReached)) => {
+                key = keys.and_then(|keys| keys.exhaust(&key)).ok_or(
+                    RequestError::Omdb(OmdbError::RequestLimitReached),
+                )?;
+            },
+            result => return result,
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
-struct OmdbError {
+struct ErrorResponse {
     error: String,
 }
 
@@ -54,7 +305,7 @@ pub struct SearchResults {
         rename(deserialize = "totalResults"),
         deserialize_with = "de_parseable"
     )]
-    pub total_results: u32, // not used or cared about currently
+    pub total_results: u32,
 }
 
 // For serialisation
@@ -73,6 +324,9 @@ pub struct SearchResult {
     pub imdb_id: String,
     #[serde(rename(deserialize = "Type"))]
     pub media_type: MediaType,
+    // Only used by machine-readable output, for showing thumbnails
+    #[serde(deserialize_with = "de_option_parseable", default)]
+    pub poster: Option<String>,
 }
 
 impl fmt::Display for SearchResult {
@@ -81,14 +335,52 @@ impl fmt::Display for SearchResult {
     }
 }
 
+impl SearchResult {
+    // How the result is shown in the TUI's list. Kept separate from Display so
+    // changes to the TUI don't leak into other output
+    pub fn list_label(&self) -> String {
+        format!("{} ({}, {})", self.title, self.media_type, self.year)
+    }
+
+    // Take the (hopefully more precise) year from the full entry
+    pub fn refine_year(&mut self, entry: &Entry) {
+        if self.imdb_id == entry.imdb_id {
+            self.year = entry.year.clone();
+        }
+    }
+}
+
+// For synthesising search results from entries, e.g. with --merge-stdin
+impl From<Entry> for SearchResult {
+    fn from(entry: Entry) -> Self {
+        SearchResult {
+            title: entry.title,
+            year: entry.year,
+            imdb_id: entry.imdb_id,
+            media_type: entry.media_type,
+            poster: entry.poster,
+        }
+    }
+}
+
 // TODO: amend options to account for games
-#[derive(Debug, Clone, Deserialize)]
+// Serialised with its own field names for --details, not OMDb's
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all(deserialize = "PascalCase"))]
 pub struct Entry {
     pub title: String,
     pub year: Year,
+    #[serde(rename(deserialize = "imdbID"))]
+    pub imdb_id: String,
     #[serde(deserialize_with = "de_option_parseable")]
-    pub runtime: Option<String>,
+    pub runtime: Option<Runtime>,
+    // Output as YYYY-MM-DD
+    #[serde(
+        deserialize_with = "de_option_released",
+        serialize_with = "ser_option_date",
+        default
+    )]
+    pub released: Option<Date>,
     #[serde(
         rename(deserialize = "Genre"),
         deserialize_with = "de_option_comma_list"
@@ -119,6 +411,28 @@ pub struct Entry {
         deserialize_with = "de_option_parseable"
     )]
     pub rating: Option<f32>,
+    #[serde(
+        rename(deserialize = "imdbVotes"),
+        deserialize_with = "de_option_votes",
+        default
+    )]
+    pub votes: Option<u32>,
+    // Out of 100
+    #[serde(deserialize_with = "de_option_metascore", default)]
+    pub metascore: Option<u8>,
+    #[serde(default)]
+    pub ratings: Vec<Rating>,
+    #[serde(deserialize_with = "de_option_awards", default)]
+    pub awards: Option<Awards>,
+    // In US dollars, only movies have this
+    #[serde(
+        rename(deserialize = "BoxOffice"),
+        deserialize_with = "de_option_currency",
+        default
+    )]
+    pub box_office: Option<u64>,
+    #[serde(deserialize_with = "de_option_parseable", default)]
+    pub poster: Option<String>,
     // #[serde(default)] as movies don't have this
     #[serde(
         rename(deserialize = "totalSeasons"),
@@ -128,6 +442,299 @@ pub struct Entry {
     pub seasons: Option<u16>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Rating {
+    pub source: RatingSource,
+    // Each source has its own scale, e.g. "8.2/10", "98%", "88/100"
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(from = "String")]
+pub enum RatingSource {
+    Imdb,
+    RottenTomatoes,
+    Metacritic,
+    Other(String),
+}
+
+impl From<String> for RatingSource {
+    fn from(source: String) -> Self {
+        use RatingSource::*;
+        match source.as_str() {
+            "Internet Movie Database" => Imdb,
+            "Rotten Tomatoes" => RottenTomatoes,
+            "Metacritic" => Metacritic,
+            _ => Other(source),
+        }
+    }
+}
+
+// Serialize using the same names as Display, e.g. "Rotten Tomatoes"
+impl Serialize for RatingSource {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl fmt::Display for RatingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RatingSource::*;
+        match self {
+            Imdb => write!(f, "IMDb"),
+            RottenTomatoes => write!(f, "Rotten Tomatoes"),
+            Metacritic => write!(f, "Metacritic"),
+            Other(source) => write!(f, "{source}"),
+        }
+    }
+}
+
+// Genres used by IMDb. Only these can be parsed, others have to be made with
+// Other
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Genre {
+    Action,
+    Adventure,
+    Animation,
+    Biography,
+    Comedy,
+    Crime,
+    Documentary,
+    Drama,
+    Family,
+    Fantasy,
+    FilmNoir,
+    GameShow,
+    History,
+    Horror,
+    Music,
+    Musical,
+    Mystery,
+    News,
+    RealityTv,
+    Romance,
+    SciFi,
+    Short,
+    Sport,
+    TalkShow,
+    Thriller,
+    War,
+    Western,
+    Other(String),
+}
+
+impl Genre {
+    const KNOWN: [Genre; 27] = {
+        use Genre::*;
+        [
+            Action,
+            Adventure,
+            Animation,
+            Biography,
+            Comedy,
+            Crime,
+            Documentary,
+            Drama,
+            Family,
+            Fantasy,
+            FilmNoir,
+            GameShow,
+            History,
+            Horror,
+            Music,
+            Musical,
+            Mystery,
+            News,
+            RealityTv,
+            Romance,
+            SciFi,
+            Short,
+            Sport,
+            TalkShow,
+            Thriller,
+            War,
+            Western,
+        ]
+    };
+
+    // As OMDb writes it
+    pub fn name(&self) -> &str {
+        use Genre::*;
+        match self {
+            Action => "Action",
+            Adventure => "Adventure",
+            Animation => "Animation",
+            Biography => "Biography",
+            Comedy => "Comedy",
+            Crime => "Crime",
+            Documentary => "Documentary",
+            Drama => "Drama",
+            Family => "Family",
+            Fantasy => "Fantasy",
+            FilmNoir => "Film-Noir",
+            GameShow => "Game-Show",
+            History => "History",
+            Horror => "Horror",
+            Music => "Music",
+            Musical => "Musical",
+            Mystery => "Mystery",
+            News => "News",
+            RealityTv => "Reality-TV",
+            Romance => "Romance",
+            SciFi => "Sci-Fi",
+            Short => "Short",
+            Sport => "Sport",
+            TalkShow => "Talk-Show",
+            Thriller => "Thriller",
+            War => "War",
+            Western => "Western",
+            Other(name) => name,
+        }
+    }
+
+    // For telling people what they could have typed instead
+    pub(crate) fn known_names() -> String {
+        Genre::KNOWN.iter().map(Genre::name).join(", ")
+    }
+}
+
+impl FromStr for Genre {
+    type Err = GenreParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Genre::KNOWN
+            .into_iter()
+            .find(|genre| genre == s)
+            .ok_or_else(|| GenreParseError(s.to_owned()))
+    }
+}
+
+// Ignores case, as OMDb and users won't always agree on it
+impl PartialEq<str> for Genre {
+    fn eq(&self, other: &str) -> bool {
+        self.name().eq_ignore_ascii_case(other)
+    }
+}
+
+impl fmt::Display for Genre {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// OMDb gives run times in minutes, like "96 min"
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Runtime(pub Duration);
+
+impl Runtime {
+    pub fn minutes(&self) -> u64 {
+        self.0.as_secs() / 60
+    }
+}
+
+impl FromStr for Runtime {
+    type Err = RuntimeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let minutes = s
+            .strip_suffix(" min")
+            .ok_or_else(|| RuntimeParseError::NoUnit(s.to_owned()))?
+            .parse::<u64>()?;
+        Ok(Runtime(Duration::from_secs(minutes * 60)))
+    }
+}
+
+// Serialize as a number of minutes, which is easier to work with than text
+impl Serialize for Runtime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.minutes())
+    }
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.minutes() {
+            1 => write!(f, "1 minute"),
+            minutes => write!(f, "{minutes} minutes"),
+        }
+    }
+}
+
+// OMDb gives awards like "Won 2 Oscars. 79 wins & 87 nominations total",
+// where the wins include any Oscars
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize)]
+pub struct Awards {
+    pub oscars_won: u32,
+    pub wins: u32,
+    pub nominations: u32,
+}
+
+impl FromStr for Awards {
+    type Err = AwardsParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let count = |regex: &Regex| {
+            regex
+                .captures(s)
+                .and_then(|captures| captures[1].parse::<u32>().ok())
+        };
+        let (oscars_won, wins, nominations) = (
+            count(&OSCARS_REGEX),
+            count(&WINS_REGEX),
+            count(&NOMINATIONS_REGEX),
+        );
+        if oscars_won.is_none() && wins.is_none() && nominations.is_none() {
+            return Err(AwardsParseError(s.to_owned()));
+        }
+        Ok(Awards {
+            oscars_won: oscars_won.unwrap_or_default(),
+            wins: wins.unwrap_or_default(),
+            nominations: nominations.unwrap_or_default(),
+        })
+    }
+}
+
+// One season of a series, as given when searching with &Season=
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Season {
+    pub title: String,
+    #[serde(deserialize_with = "de_parseable")]
+    pub season: u16,
+    #[serde(
+        rename(deserialize = "totalSeasons"),
+        deserialize_with = "de_parseable"
+    )]
+    pub total_seasons: u16,
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Episode {
+    pub title: String,
+    #[serde(deserialize_with = "de_parseable")]
+    pub episode: u16,
+    #[serde(rename(deserialize = "imdbID"))]
+    pub imdb_id: String,
+    #[serde(deserialize_with = "de_option_parseable", default)]
+    pub released: Option<String>,
+    #[serde(
+        rename(deserialize = "imdbRating"),
+        deserialize_with = "de_option_parseable",
+        default
+    )]
+    pub rating: Option<f32>,
+}
+
 /*
 Lists in OMDb are given like "Pete Docter, Bob Peterson, Tom McCarthy"
 This helper could throw that into a Vec<String>
@@ -187,6 +794,99 @@ where
     Ok(t)
 }
 
+// Vote counts have thousands separators, like "966,025"
+fn de_option_votes<'de, D>(d: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    let option = if s != "N/A" {
+        let votes = s.replace(',', "").parse().map_err(D::Error::custom)?;
+        Some(votes)
+    } else {
+        None
+    };
+    Ok(option)
+}
+
+// Awards are only extra information, so any that can't be understood are
+// left out instead of failing the whole entry
+fn de_option_awards<'de, D>(d: D) -> Result<Option<Awards>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    Ok(s.parse().ok())
+}
+
+// Anything above 100 means OMDb has changed what it gives
+fn de_option_metascore<'de, D>(d: D) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match de_option_parseable(d)? {
+        Some(score) if score > 100 => Err(D::Error::custom(format!(
+            "metascore {score} isn't out of 100"
+        ))),
+        option => Ok(option),
+    }
+}
+
+// "$293,004,164" => 293004164. None if it isn't an amount of dollars
+pub fn parse_currency(s: &str) -> Option<u64> {
+    let digits = s.strip_prefix('$')?.replace(',', "");
+    // parse would allow a leading +
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn de_option_currency<'de, D>(d: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    let option = if s != "N/A" {
+        let amount = parse_currency(&s).ok_or_else(|| {
+            D::Error::custom(format!("{s:?} isn't an amount of dollars"))
+        })?;
+        Some(amount)
+    } else {
+        None
+    };
+    Ok(option)
+}
+
+// e.g. "29 May 2009"
+const RELEASED_FORMAT: &[FormatItem] =
+    format_description!("[day] [month repr:short] [year]");
+
+fn de_option_released<'de, D>(d: D) -> Result<Option<Date>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(d)?;
+    let option = if s != "N/A" {
+        let date =
+            Date::parse(&s, RELEASED_FORMAT).map_err(D::Error::custom)?;
+        Some(date)
+    } else {
+        None
+    };
+    Ok(option)
+}
+
+fn ser_option_date<S>(date: &Option<Date>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match date {
+        Some(date) => s.serialize_str(&date.to_string()),
+        None => s.serialize_none(),
+    }
+}
+
 /*
 OMDb sometimes (not always, not never) includes fields that it doesn't have
 anything useful to provide for, giving the value of said fields as "N/A". This
@@ -230,37 +930,43 @@ impl MediaType {
         movie + series + game
     }
 
-    fn str_iter(&self) -> impl Iterator<Item = &'static str> {
-        let movie = if self.contains(MediaType::MOVIE) {
-            Some("movie")
-        } else {
-            None
-        };
-        let series = if self.contains(MediaType::SERIES) {
-            Some("series")
-        } else {
-            None
-        };
-        let game = if self.contains(MediaType::GAME) {
-            Some("game")
-        } else {
-            None
-        };
-        [movie, series, game].into_iter().flatten()
+    pub const fn is_movie(&self) -> bool {
+        self.contains(MediaType::MOVIE)
     }
-}
 
-// Note: multiple types at once are not supported
-impl FromStr for MediaType {
-    type Err = MediaTypeParseError;
+    pub const fn is_series(&self) -> bool {
+        self.contains(MediaType::SERIES)
+    }
+
+    pub const fn is_game(&self) -> bool {
+        self.contains(MediaType::GAME)
+    }
+
+    // The individual types are iterated over with iter, which bitflags
+    // provides. This gives the name OMDb uses for each
+    fn str_iter(&self) -> impl Iterator<Item = &'static str> {
+        self.iter().map(|media_type| match media_type {
+            MediaType::MOVIE => "movie",
+            MediaType::SERIES => "series",
+            _ => "game",
+        })
+    }
+}
+
+// Multiple types can be given separated by commas, e.g. movie,series
+impl FromStr for MediaType {
+    type Err = MediaTypeParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_ascii_lowercase().as_str() {
-            "movie" | "movies" => Ok(MediaType::MOVIE),
-            "series" => Ok(MediaType::SERIES),
-            "game" => Ok(MediaType::GAME),
-            _ => Err(MediaTypeParseError(s.to_owned())),
-        }
+        s.split(',').try_fold(MediaType::empty(), |types, token| {
+            let media_type = match token.trim().to_ascii_lowercase().as_str() {
+                "movie" | "movies" => MediaType::MOVIE,
+                "series" => MediaType::SERIES,
+                "game" => MediaType::GAME,
+                _ => return Err(MediaTypeParseError(token.trim().to_owned())),
+            };
+            Ok(types | media_type)
+        })
     }
 }
 
@@ -294,27 +1000,50 @@ impl fmt::Display for MediaType {
     }
 }
 
-// Serialize using string representation
+// Serialize a single type as its string representation, and multiple types as
+// an array of them
 // Only used for machine-readable outputs (--format)
 impl Serialize for MediaType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let string = self.to_string();
-        serializer.serialize_str(&string)
+        match self.count() {
+            1 => serializer.serialize_str(&self.to_string()),
+            _ => serializer.collect_seq(self.str_iter()),
+        }
     }
 }
 
+// Either form that MediaType is serialized as, or the slash separated
+// string that multiple types used to be serialized as
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MediaTypeRepr {
+    One(String),
+    Many(Vec<String>),
+}
+
 // Deserialize with FromStr
 impl<'de> Deserialize<'de> for MediaType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        String::deserialize(deserializer)?
-            .parse()
-            .map_err(D::Error::custom)
+        let strings = match MediaTypeRepr::deserialize(deserializer)? {
+            MediaTypeRepr::One(string) => {
+                string.split('/').map(str::to_owned).collect()
+            },
+            MediaTypeRepr::Many(strings) => strings,
+        };
+        if strings.is_empty() {
+            return Err(D::Error::custom("no media types given"));
+        }
+        strings.iter().try_fold(MediaType::empty(), |types, s| {
+            let media_type =
+                s.parse::<MediaType>().map_err(D::Error::custom)?;
+            Ok(types | media_type)
+        })
     }
 }
 
@@ -364,49 +1093,174 @@ impl fmt::Display for FilterParameters {
     }
 }
 
+/// The results of a search, along with how many OMDb says there are in total
+/// (which can be many more than were fetched)
 #[derive(Debug)]
+pub struct Matches {
+    pub results: Vec<SearchResult>,
+    pub total: u32,
+}
+
+/// Searches and lookups on OMDb, for using imdb-id as a library. Warnings
+/// (like one of a search's requests failing) are logged with the `log` crate
+/// rather than printed, so are only seen if there's a logger
+///
+/// ```
+/// use imdb_id::omdb::Client;
+/// use imdb_id::{Filters, RequestError};
+///
+/// // Anything that gives OMDb's response to a request can stand in for it
+/// let omdb = |_request: minreq::Request| -> Result<String, RequestError> {
+///     Ok(r#"{"Search":[{"Title":"Up","Year":"2009","imdbID":"tt1049413","Type":"movie","Poster":"N/A"}],"totalResults":"1","Response":"True"}"#.to_owned())
+/// };
+/// let client = Client::with_omdb("abc123", omdb);
+/// let results = client.search("up", &Filters::default())?;
+/// assert_eq!(results[0].title, "Up");
+/// assert_eq!(results[0].imdb_id, "tt1049413");
+/// # Ok::<(), RequestError>(())
+/// ```
+pub struct Client {
+    api_key: String,
+    omdb: Box<dyn OmdbClient>,
+    max_requests: usize,
+}
+
+impl Client {
+    // One page of OMDb's results
+    const SEARCH_RESULTS: usize = 10;
+
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Client::with_omdb(api_key, Minreq)
+    }
+
+    pub fn with_omdb(
+        api_key: impl Into<String>,
+        omdb: impl OmdbClient + 'static,
+    ) -> Self {
+        Client {
+            api_key: api_key.into(),
+            omdb: Box::new(omdb),
+            max_requests: DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        }
+    }
+
+    /// The most requests made for a single search (10 unless changed)
+    pub fn with_max_requests(mut self, max_requests: usize) -> Self {
+        self.max_requests = max_requests;
+        self
+    }
+
+    /// For searches that need more control than [`Client::search`] gives,
+    /// like how many results to get, or reading more pages later
+    pub fn bundle<'a>(
+        &'a self,
+        title: &'a str,
+        filters: &'a Filters,
+    ) -> RequestBundle<'a> {
+        RequestBundle::with_client(
+            self.omdb.as_ref(),
+            &self.api_key,
+            title,
+            filters,
+            self.max_requests,
+        )
+    }
+
+    /// Searches for the title, giving up to a page's worth of results
+    pub fn search(
+        &self,
+        title: &str,
+        filters: &Filters,
+    ) -> Result<Vec<SearchResult>, RequestError> {
+        self.bundle(title, filters)
+            .get_results(Client::SEARCH_RESULTS, false)
+            .map(|matches| matches.results)
+    }
+
+    /// Gets the full details of the media with the given IMDb ID
+    pub fn entry(&self, imdb_id: &str) -> Result<Entry, RequestError> {
+        let settings = request_settings();
+        get_entry_with(
+            self.omdb.as_ref(),
+            &self.api_key,
+            imdb_id,
+            false,
+            settings.entry_cache.as_ref(),
+            settings.api_keys.as_ref(),
+        )
+    }
+}
+
 pub struct RequestBundle<'a> {
+    client: &'a dyn OmdbClient,
     api_key: &'a str,
+    keys: Option<&'a ApiKeys>,
     title: Cow<'a, str>,
     params: SmallVec<[FilterParameters; DEFAULT_MAX_REQUESTS_PER_SEARCH]>,
+    filters: &'a Filters,
+    max_requests: usize,
+    last_page: Cell<u32>,
+    // Told how many requests have been made, out of the most that could be,
+    // as each one finishes
+    progress: Option<&'a (dyn Fn(usize, usize) + Sync)>,
 }
 
 impl<'a> RequestBundle<'a> {
-    pub fn new(api_key: &'a str, title: &'a str, filters: &'a Filters) -> Self {
+    pub fn new(
+        api_key: &'a str,
+        title: &'a str,
+        filters: &'a Filters,
+        max_requests: usize,
+    ) -> Self {
+        RequestBundle::with_client(
+            &Minreq,
+            api_key,
+            title,
+            filters,
+            max_requests,
+        )
+    }
+
+    pub fn with_client(
+        client: &'a dyn OmdbClient,
+        api_key: &'a str,
+        title: &'a str,
+        filters: &'a Filters,
+        max_requests: usize,
+    ) -> Self {
         let combinations = filters.combinations();
-        if combinations > *MAX_REQUESTS_PER_SEARCH {
-            eprintln!(
-                "WARNING: the combination of filters you've specified \
-            can't be exhaustively covered in {} requests (it would take \
-            {combinations} requests), so some results will be missed. You can \
-            set the IMDB_ID_MAX_REQUESTS_PER_SEARCH environment variable to \
-            change this number",
-                *MAX_REQUESTS_PER_SEARCH
+        if combinations > max_requests {
+            log::warn!(
+                "the combination of filters you've specified \
+            can't be exhaustively covered in {max_requests} requests (it would \
+            take {combinations} requests), so some results will be missed. You \
+            can use --max-requests (or set the IMDB_ID_MAX_REQUESTS_PER_SEARCH \
+            environment variable) to change this number"
             );
         }
 
-        let params = match (filters.types, filters.years.as_ref()) {
-            (MediaType::ALL, None) => {
+        let years = filters.search_years();
+        let params = match (filters.types, years.is_empty()) {
+            (MediaType::ALL, true) => {
                 // No filters at all
                 smallvec![FilterParameters::default()]
             },
-            (MediaType::ALL, Some(years)) => {
+            (MediaType::ALL, false) => {
                 // Just years specified
                 years
-                    .0
-                    .clone()
-                    .take(*MAX_REQUESTS_PER_SEARCH)
+                    .into_iter()
+                    .take(max_requests)
                     .map(FilterParameters::from)
                     .collect::<SmallVec<_>>()
             },
-            (types, None) => {
+            (types, true) => {
                 // Just media type specified
                 types
                     .str_iter()
                     .map(FilterParameters::from)
                     .collect::<SmallVec<_>>()
             },
-            (types, Some(years)) => {
+            (types, false) => {
                 // Both years and media type specified
                 // Massage types so it satisfies itertools' requirements
                 let types = types
@@ -414,76 +1268,259 @@ impl<'a> RequestBundle<'a> {
                     .map(ToOwned::to_owned)
                     .collect::<SmallVec<[String; 3]>>();
                 years
-                    .0
-                    .clone()
+                    .into_iter()
                     .cartesian_product(types)
-                    .take(*MAX_REQUESTS_PER_SEARCH)
+                    .take(max_requests)
                     .map(FilterParameters::from)
                     .collect::<SmallVec<_>>()
             },
         };
         RequestBundle {
+            client,
             api_key,
+            keys: request_settings().api_keys.as_ref(),
             title: urlencoding::encode(title),
             params,
+            filters,
+            max_requests,
+            last_page: Cell::new(0),
+            progress: None,
+        }
+    }
+
+    /// A new search for a different title, with the same filters and limit
+    /// on requests
+    pub fn search_again<'b>(&'b self, title: &'b str) -> RequestBundle<'b> {
+        RequestBundle::with_client(
+            self.client,
+            self.api_key,
+            title,
+            self.filters,
+            self.max_requests,
+        )
+    }
+
+    pub fn with_progress(
+        self,
+        progress: &'a (dyn Fn(usize, usize) + Sync),
+    ) -> Self {
+        RequestBundle {
+            progress: Some(progress),
+            ..self
         }
     }
 
+    /// Gets at least `wanted` results if possible, reading further pages of
+    /// each search as needed. No more than the bundle's maximum number of
+    /// requests are made in total
     pub fn get_results(
         &self,
+        wanted: usize,
+        allow_reading_time: bool,
+    ) -> Result<Matches, RequestError> {
+        let matches = self.get_pages(
+            1,
+            wanted,
+            self.max_requests,
+            allow_reading_time,
+            |request| send_request_deserialise(self.client, request),
+        );
+        if let Err(RequestError::Omdb(OmdbError::NotFound)) = &matches {
+            let suggestions = self.suggestions();
+            if !suggestions.is_empty() {
+                let titles = suggestions.iter().join(", ");
+                log::warn!("No exact matches; did you mean: {titles}");
+            }
+        }
+        matches
+    }
+
+    // Titles the search would've found without its year and type filters.
+    // Only ever a single request, and never more than once, as the search
+    // made for them has no filters of its own
+    fn suggestions(&self) -> Vec<SearchResult> {
+        const SUGGESTIONS: usize = 3;
+        if self.filters.types == MediaType::ALL && self.filters.years.is_empty()
+        {
+            return Vec::new();
+        }
+        let filters = Filters::default();
+        let loosened = RequestBundle {
+            client: self.client,
+            api_key: self.api_key,
+            keys: self.keys,
+            title: Cow::Borrowed(&self.title),
+            params: smallvec![FilterParameters::default()],
+            filters: &filters,
+            max_requests: 1,
+            last_page: Cell::new(0),
+            progress: None,
+        };
+        match loosened.get_pages(1, SUGGESTIONS, 1, false, |request| {
+            send_request_deserialise(self.client, request)
+        }) {
+            Ok(Matches { mut results, .. }) => {
+                results.truncate(SUGGESTIONS);
+                results
+            },
+            Err(why) => {
+                log::debug!("couldn't look for other titles: {why}");
+                Vec::new()
+            },
+        }
+    }
+
+    /// Gets the given page of results for each search (starting from 1),
+    /// merged together. OMDb gives 10 results per page
+    pub fn get_page(
+        &self,
+        page: u32,
         allow_reading_time: bool,
     ) -> Result<Vec<SearchResult>, RequestError> {
-        let mut result_sets = Vec::with_capacity(self.params.len());
+        self.get_pages(
+            page,
+            0,
+            self.params.len(),
+            allow_reading_time,
+            |request| send_request_deserialise(self.client, request),
+        )
+        .map(|matches| matches.results)
+    }
+
+    /// The first page that hasn't been fully read by get_results
+    pub fn next_page(&self) -> u32 {
+        self.last_page.get() + 1
+    }
+
+    // Reads a page from every search at a time (skipping searches that have
+    // run out) until there are enough results, or the requests run out.
+    // Each page's requests are sent concurrently with `send`, so tests don't
+    // need the network
+    fn get_pages(
+        &self,
+        first_page: u32,
+        wanted: usize,
+        mut requests_left: usize,
+        allow_reading_time: bool,
+        send: impl Fn(Request) -> Result<SearchResults, RequestError> + Sync,
+    ) -> Result<Matches, RequestError> {
+        let most_requests = requests_left;
+        let made = AtomicUsize::new(0);
+        let progress = self.progress;
+        let send = |request| {
+            let response = send(request);
+            if let Some(progress) = progress {
+                let made = made.fetch_add(1, Ordering::Relaxed) + 1;
+                progress(made, most_requests);
+            }
+            response
+        };
+        // Each search's results, with pages one after the other
+        let mut result_sets =
+            vec![None::<Vec<SearchResult>>; self.params.len()];
+        let mut exhausted = vec![false; self.params.len()];
+        // Each search's total according to OMDb. The searches don't overlap,
+        // so these add up to the total for the whole thing
+        let mut totals = vec![0; self.params.len()];
         // Number of milliseconds to allow the user to read any warnings they
         // get. Additional time added for each error message
         let mut reading_time = 0;
 
         let mut no_results_err = None;
 
-        for params in self.params.iter() {
-            // Build request
-            let request =
-                base_query(self.api_key).with_param("s", self.title.as_ref());
-            let request = match &params.media_type {
-                Some(mt) => request.with_param("type", mt.to_string()),
-                None => request,
-            };
-            let request = match params.year {
-                Some(year) => request.with_param("y", year.to_string()),
-                None => request,
-            };
-            // Send request
-            match send_omdb_search(request) {
-                Ok(results) => result_sets.push(results.entries),
-                Err(missing) if matches!(&missing, RequestError::Omdb(msg) if msg.ends_with("not found!")) => {
-                    no_results_err = Some(missing)
-                },
-                Err(fatal) if fatal.is_fatal() => return Err(fatal),
-                Err(warn) => {
-                    eprintln!("Problem with request ({params}): {warn}");
-                    reading_time += 200;
-                },
+        let mut page = first_page;
+        loop {
+            let mut out_of_requests = false;
+            let mut searches = Vec::with_capacity(self.params.len());
+            for (index, params) in self.params.iter().enumerate() {
+                if exhausted[index] {
+                    continue;
+                }
+                if requests_left == 0 {
+                    out_of_requests = true;
+                    break;
+                }
+                requests_left -= 1;
+                searches.push((index, params));
+            }
+
+            // Keys that reached their limit on the last page aren't tried again
+            let api_key = usable_key(self.keys, self.api_key)?;
+            let requests = searches
+                .iter()
+                .map(|(_, params)| self.request(&api_key, params, page))
+                .collect();
+            // Responses are handled in the order of the searches, not the
+            // order they arrive in, so the results are always the same
+            let responses = send_concurrently(requests, &send);
+            for ((index, params), response) in
+                searches.into_iter().zip(responses)
+            {
+                let response = match (response, self.keys) {
+                    (
+                        Err(RequestError::Omdb(OmdbError::RequestLimitReached)),
+                        Some(keys),
+                    ) => {
+                        keys.exhaust(&api_key);
+                        with_failover(self.keys, &api_key, |key| {
+                            send(self.request(key, params, page))
+                        })
+                    },
+                    (response, _) => response,
+                };
+                match response {
+                    Ok(results) => {
+                        totals[index] = results.total_results;
+                        let set =
+                            result_sets[index].get_or_insert_with(Vec::new);
+                        exhausted[index] = add_page(set, results);
+                    },
+                    // Said instead of giving no results at all
+                    Err(
+                        missing @ RequestError::Omdb(
+                            OmdbError::NotFound | OmdbError::TooManyResults,
+                        ),
+                    ) => {
+                        no_results_err = Some(missing);
+                        exhausted[index] = true;
+                    },
+                    Err(fatal) if fatal.is_fatal() => return Err(fatal),
+                    Err(warn) => {
+                        log::warn!("problem with request ({params}): {warn}");
+                        reading_time += 200;
+                        exhausted[index] = true;
+                    },
+                }
             }
+            if out_of_requests {
+                break;
+            }
+            // Every search got this page
+            self.last_page.set(self.last_page.get().max(page));
+
+            let mut seen = Seen::default();
+            let found = result_sets
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|sr| self.filters.allows(sr) && seen.first(sr))
+                .count();
+            log::debug!("{found} of {wanted} results found after page {page}");
+            if found >= wanted || exhausted.iter().all(|&done| done) {
+                break;
+            }
+            page += 1;
         }
 
         // Only throw no results error if all searches returned nothing
+        let result_sets = result_sets.into_iter().flatten().collect::<Vec<_>>();
         if result_sets.is_empty() {
-            return Err(no_results_err.unwrap());
+            return Err(
+                no_results_err.unwrap_or(RequestError::AllRequestsFailed)
+            );
         }
 
-        // Merge results
-        let results = result_sets
-            .into_iter()
-            .map(|set| set.into_iter().enumerate())
-            // Merge results for different searches based on their rankings
-            // from their own search. The end result should be all the first
-            // results, then all the second results, etc.
-            .kmerge_by(|a, b| a.0 < b.0)
-            .map(|(_, sr)| sr)
-            // I've noticed some duplicates coming through even from the API
-            // directly, so might as well use itertools now I have it
-            .unique_by(|sr| sr.imdb_id.clone())
-            .collect::<Vec<SearchResult>>();
+        let results = self.apply_filters(merge_result_sets(result_sets));
 
         // No need to give reading time if there are no results as the TUI
         // won't be opened
@@ -491,28 +1528,498 @@ impl<'a> RequestBundle<'a> {
             thread::sleep(Duration::from_millis(reading_time));
         }
 
-        Ok(results)
+        Ok(Matches {
+            results,
+            total: totals.into_iter().sum(),
+        })
+    }
+
+    // The request parameters only roughly match the filters (e.g. a series
+    // only overlapping the years at one end), so make sure the results are
+    // exactly what was asked for
+    fn apply_filters(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+        results
+            .into_iter()
+            .filter(|sr| self.filters.allows(sr))
+            .collect()
+    }
+
+    fn request(
+        &self,
+        api_key: &str,
+        params: &FilterParameters,
+        page: u32,
+    ) -> Request {
+        let query = self.query(params, page);
+        let query = query
+            .iter()
+            .map(|(key, value)| (*key, value.as_ref()))
+            .collect::<Vec<_>>();
+        base_query(api_key, &query)
+    }
+
+    fn query<'b>(
+        &'b self,
+        params: &'b FilterParameters,
+        page: u32,
+    ) -> Vec<(&'static str, Cow<'b, str>)> {
+        let mut query = vec![("s", Cow::Borrowed(self.title.as_ref()))];
+        if let Some(mt) = &params.media_type {
+            query.push(("type", Cow::Borrowed(mt.as_ref())));
+        }
+        if let Some(year) = params.year {
+            query.push(("y", Cow::Owned(year.to_string())));
+        }
+        if page != 1 {
+            query.push(("page", Cow::Owned(page.to_string())));
+        }
+        query
+    }
+
+    /// The URLs of the requests for the first page of results, with the API
+    /// key hidden. Nothing is sent
+    pub fn dry_run(&self) -> Vec<String> {
+        self.params
+            .iter()
+            .map(|params| {
+                let query = self.query(params, 1);
+                let query = query
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_ref()))
+                    .collect::<Vec<_>>();
+                format!(
+                    "https://www.omdbapi.com/?apikey=********&{}",
+                    shown_query(&query)
+                )
+            })
+            .collect()
+    }
+}
+
+// Sends up to CONCURRENT_REQUESTS requests at a time, giving the responses back
+// in the same order as the requests
+fn send_concurrently<T: Send>(
+    requests: Vec<Request>,
+    send: &(impl Fn(Request) -> Result<T, RequestError> + Sync),
+) -> Vec<Result<T, RequestError>> {
+    let mut responses = Vec::with_capacity(requests.len());
+    let mut requests = requests.into_iter().peekable();
+    while requests.peek().is_some() {
+        let chunk = requests.by_ref().take(CONCURRENT_REQUESTS);
+        responses.extend(fetch_concurrently(chunk, send));
+    }
+    responses
+}
+
+// Fetches all the items at once, each on its own thread, giving the results
+// back in the same order as the items. It's up to the caller to only give
+// CONCURRENT_REQUESTS items at a time
+fn fetch_concurrently<I: Send, T: Send>(
+    items: impl IntoIterator<Item = I>,
+    fetch: &(impl Fn(I) -> T + Sync),
+) -> Vec<T> {
+    thread::scope(|scope| {
+        items
+            .into_iter()
+            .map(|item| scope.spawn(move || fetch(item)))
+            // Collect so that all the requests are started before waiting on
+            // any of them
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().expect("request thread panicked"))
+            .collect()
+    })
+}
+
+// Pages go one after the other, so a search's page 1 results are always
+// ranked above its page 2 results when merging. Returns whether the search has
+// run out of results
+fn add_page(set: &mut Vec<SearchResult>, page: SearchResults) -> bool {
+    set.extend(page.entries);
+    set.len() >= page.total_results as usize
+}
+
+// Merge results for different searches based on their rankings from their own
+// search. The end result should be all the first results, then all the second
+// results, etc. Results of the same rank are ordered by which search they came
+// from, and once a search runs out of results the others carry on interleaving
+// e.g. [A0], [B0, B1], [C0, C1, C2] => A0 B0 C0 B1 C1 C2
+fn merge_result_sets(result_sets: Vec<Vec<SearchResult>>) -> Vec<SearchResult> {
+    let merged = result_sets
+        .into_iter()
+        .enumerate()
+        .map(|(set_index, set)| {
+            set.into_iter()
+                .enumerate()
+                .map(move |(rank, sr)| ((rank, set_index), sr))
+        })
+        // Keys are unique, so the order is fully determined
+        .kmerge_by(|a, b| a.0 < b.0)
+        .map(|(_, sr)| sr)
+        .collect();
+    remove_duplicates(merged)
+}
+
+// Keeps the first of each result, in order
+fn remove_duplicates(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    // Work out what to keep while borrowing, so no IDs need cloning
+    let mut seen = Seen::default();
+    let keep = results.iter().map(|sr| seen.first(sr)).collect::<Vec<_>>();
+    results
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(sr, keep)| keep.then_some(sr))
+        .collect()
+}
+
+// I've noticed some duplicates coming through even from the API directly,
+// sometimes under a different ID with the same title and year. The media type
+// is checked too, as a movie and a game often share a title and year
+#[derive(Default)]
+struct Seen<'a> {
+    ids: HashSet<&'a str>,
+    titles: HashSet<(&'a str, u16, u16, u8)>,
+}
+
+impl<'a> Seen<'a> {
+    // Whether this is the first time the result (or one like it) was seen
+    fn first(&mut self, sr: &'a SearchResult) -> bool {
+        let title = (
+            sr.title.as_str(),
+            *sr.year.0.start(),
+            *sr.year.0.end(),
+            sr.media_type.bits(),
+        );
+        self.ids.insert(&sr.imdb_id) && self.titles.insert(title)
+    }
+}
+
+/// Times fetching and merging pages of synthetic results, without making any
+/// requests. Used by the hidden --benchmark flag
+pub fn benchmark() {
+    const PAGES_PER_SEARCH: usize = 100;
+    const RUNS: u32 = 5;
+    // Ten searches, one for each year
+    let filters = Filters {
+        types: MediaType::ALL,
+        years: vec![Year(2000..=2009)],
+        ..Default::default()
+    };
+    let bundle =
+        RequestBundle::new("", "", &filters, DEFAULT_MAX_REQUESTS_PER_SEARCH);
+    let requests = bundle.params.len() * PAGES_PER_SEARCH;
+    let total = requests * 10;
+
+    let mut unique = 0;
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let sent = AtomicUsize::new(0);
+        // Around half of the results are duplicates
+        let send = |_| {
+            let sent = sent.fetch_add(1, Ordering::Relaxed);
+            let entries = (0..10)
+                .map(|rank| {
+                    let id = (sent * 10 + rank) % (total / 2);
+                    SearchResult {
+                        title: format!("Result {id}"),
+                        imdb_id: format!("tt{id:07}"),
+                        media_type: MediaType::MOVIE,
+                        year: Year(2005..=2005),
+                        poster: None,
+                    }
+                })
+                .collect();
+            Ok(SearchResults {
+                entries,
+                total_results: total as u32,
+            })
+        };
+        let results = bundle
+            .get_pages(1, usize::MAX, requests, false, send)
+            .expect("synthetic requests can't fail");
+        unique = results.results.len();
+    }
+    let per_run = start.elapsed() / RUNS;
+    let rate = total as f64 / per_run.as_secs_f64();
+    println!(
+        "Merged {total} results ({unique} unique) from {requests} pages in \
+        {per_run:?} per run ({rate:.0} results/s, {RUNS} runs)"
+    );
+}
+
+/// The first result for one of the titles given to --batch
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub search_term: String,
+    pub result: Option<SearchResult>,
+}
+
+/// Searches for each title in turn. Titles that OMDb has nothing for (or
+/// that have non-fatal problems) get no result rather than stopping the batch
+pub fn batch_search(
+    api_key: &str,
+    search_terms: &[String],
+    filters: &Filters,
+    max_requests: usize,
+) -> Result<Vec<BatchResult>, RequestError> {
+    batch_search_with(api_key, search_terms, filters, max_requests, |request| {
+        send_request_deserialise(&Minreq, request)
+    })
+}
+
+fn batch_search_with(
+    api_key: &str,
+    search_terms: &[String],
+    filters: &Filters,
+    max_requests: usize,
+    send: impl Fn(Request) -> Result<SearchResults, RequestError> + Sync,
+) -> Result<Vec<BatchResult>, RequestError> {
+    search_terms
+        .iter()
+        .map(|search_term| {
+            let bundle =
+                RequestBundle::new(api_key, search_term, filters, max_requests);
+            let result =
+                match bundle.get_pages(1, 1, max_requests, false, &send) {
+                    Ok(matches) => matches.results.into_iter().next(),
+                    Err(RequestError::Omdb(why)) if !why.is_fatal() => {
+                        log::warn!("no results for {search_term:?}: {why}");
+                        None
+                    },
+                    Err(fatal) if fatal.is_fatal() => return Err(fatal),
+                    Err(warn) => {
+                        log::warn!(
+                            "couldn't search for {search_term:?}: {warn}"
+                        );
+                        None
+                    },
+                };
+            Ok(BatchResult {
+                search_term: search_term.clone(),
+                result,
+            })
+        })
+        .collect()
+}
+
+/// Combines two lists of results, keeping the order of `first` and then
+/// `second`, removing any duplicates
+pub fn merge_results(
+    first: Vec<SearchResult>,
+    second: Vec<SearchResult>,
+) -> Vec<SearchResult> {
+    remove_duplicates(first.into_iter().chain(second).collect())
+}
+
+/// The orders results can be put in. Rating isn't one of them, as that would
+/// need a request per result
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum SortKey {
+    /// The order OMDb gives them in
+    #[default]
+    Relevance,
+    /// Oldest first, by the year they started
+    Year,
+    /// Newest first, by the year they started
+    YearDesc,
+    /// Alphabetically, ignoring case
+    Title,
+}
+
+impl FromStr for SortKey {
+    type Err = SortKeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "relevance" => Ok(SortKey::Relevance),
+            "year" => Ok(SortKey::Year),
+            "year-desc" => Ok(SortKey::YearDesc),
+            "title" => Ok(SortKey::Title),
+            _ => Err(SortKeyParseError(s.to_owned())),
+        }
+    }
+}
+
+/// Puts the results in the given order. Sorts are stable, so ties stay in
+/// relevance order
+pub fn sort_results(search_results: &mut [SearchResult], key: SortKey) {
+    match key {
+        SortKey::Relevance => {},
+        SortKey::Year => search_results.sort_by_key(|sr| *sr.year.0.start()),
+        SortKey::YearDesc => {
+            search_results.sort_by_key(|sr| Reverse(*sr.year.0.start()))
+        },
+        SortKey::Title => {
+            search_results.sort_by_cached_key(|sr| sr.title.to_lowercase())
+        },
+    }
+}
+
+/// Puts the results with titles most like the search term first, for --fuzzy.
+/// Exact matches (ignoring case) always come first, and ties stay in relevance
+/// order
+pub fn rank_by_similarity(
+    search_results: &mut [SearchResult],
+    search_term: &str,
+) {
+    let search_term = search_term.trim().to_lowercase();
+    search_results.sort_by_cached_key(|sr| {
+        let title = sr.title.trim().to_lowercase();
+        // Floats can't be sort keys, so scale the score up to a whole number
+        let score =
+            (normalized_levenshtein(&title, &search_term) * 1_000_000.0) as u32;
+        (title != search_term, Reverse(score))
+    });
+}
+
+/// Keeps only the results titled exactly the search term (ignoring case), for
+/// --exact. If there aren't any, gives the same error as OMDb does when
+/// nothing matches
+pub fn keep_exact_titles(
+    search_results: Vec<SearchResult>,
+    search_term: &str,
+) -> Result<Vec<SearchResult>, RequestError> {
+    let search_term = search_term.trim().to_lowercase();
+    let exact = search_results
+        .into_iter()
+        .filter(|sr| sr.title.trim().to_lowercase() == search_term)
+        .collect::<Vec<_>>();
+    if exact.is_empty() {
+        Err(RequestError::Omdb(OmdbError::NotFound))
+    } else {
+        Ok(exact)
+    }
+}
+
+/// Keeps only the results with the minimum rating and one of the genres (if
+/// given), stopping once there are `wanted` of them. Checking a result takes
+/// a request, and a few are made at a time
+pub fn filter_by_entry(
+    api_key: &str,
+    search_results: Vec<SearchResult>,
+    filters: &Filters,
+    wanted: usize,
+) -> Vec<SearchResult> {
+    if !filters.needs_entries() {
+        return search_results;
+    }
+    let mut kept = Vec::with_capacity(wanted);
+    for chunk in search_results.chunks(CONCURRENT_REQUESTS) {
+        if kept.len() >= wanted {
+            break;
+        }
+        let entries = fetch_concurrently(chunk, &|sr: &SearchResult| {
+            get_entry(api_key, &sr.imdb_id)
+        });
+        for (sr, entry) in chunk.iter().zip(entries) {
+            match entry {
+                Ok(entry)
+                    if filters.allows_rating(entry.rating)
+                        && filters.allows_genres(entry.genres.as_deref())
+                        && filters
+                            .allows_languages(entry.language.as_deref()) =>
+                {
+                    kept.push(sr.clone())
+                },
+                Ok(_) => {},
+                Err(why) => {
+                    log::warn!("couldn't check the details of {sr}: {why}")
+                },
+            }
+        }
+    }
+    kept
+}
+
+// For --details, falling back on the search result if its entry couldn't be
+// fetched
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Details {
+    Entry(Box<Entry>),
+    Result(SearchResult),
+}
+
+pub fn get_details(
+    api_key: &str,
+    search_results: &[SearchResult],
+) -> Vec<Details> {
+    get_details_with(search_results, |imdb_id| get_entry(api_key, imdb_id))
+}
+
+fn get_details_with(
+    search_results: &[SearchResult],
+    fetch: impl Fn(&str) -> Result<Entry, RequestError> + Sync,
+) -> Vec<Details> {
+    let mut details = Vec::with_capacity(search_results.len());
+    for chunk in search_results.chunks(CONCURRENT_REQUESTS) {
+        let entries =
+            fetch_concurrently(chunk, &|sr: &SearchResult| fetch(&sr.imdb_id));
+        for (sr, entry) in chunk.iter().zip(entries) {
+            match entry {
+                Ok(entry) => details.push(Details::Entry(Box::new(entry))),
+                Err(why) => {
+                    log::warn!(
+                        "couldn't get the details of {sr}, so only \
+                        its search result is given: {why}"
+                    );
+                    details.push(Details::Result(sr.clone()));
+                },
+            }
+        }
     }
+    details
+}
+
+/// Fetches the entries of series with open-ended years to get a more precise
+/// year. At most `limit` entries will be fetched
+pub fn resolve_years(
+    api_key: &str,
+    search_results: &mut [SearchResult],
+    limit: usize,
+) {
+    search_results
+        .iter_mut()
+        .filter(|sr| {
+            sr.media_type == MediaType::SERIES && sr.year.is_open_ended()
+        })
+        .take(limit)
+        .for_each(|sr| match get_entry(api_key, &sr.imdb_id) {
+            Ok(entry) => sr.refine_year(&entry),
+            Err(why) => {
+                log::warn!("couldn't resolve the year of {sr}: {why}")
+            },
+        });
 }
 
 pub fn test_api_key(api_key: &str) -> Result<(), ApiKeyError> {
+    test_api_key_with(&Minreq, api_key)
+}
+
+fn test_api_key_with(
+    client: &dyn OmdbClient,
+    api_key: &str,
+) -> Result<(), ApiKeyError> {
     use ApiKeyError::*;
 
     if !api_key_format_acceptable(api_key) {
         return Err(InvalidFormat);
     }
 
-    let status = minreq::get("https://www.omdbapi.com/")
-        .with_param("apikey", api_key)
-        .send()?
-        .status_code;
-
-    if status.eq(&200) {
-        Ok(())
-    } else if status.eq(&401) {
-        Err(Unauthorised)
-    } else {
-        Err(UnexpectedStatus(status))
+    log::debug!("Checking the API key with https://www.omdbapi.com/");
+    let request = get("https://www.omdbapi.com/").with_param("apikey", api_key);
+    // Without anything to look up, OMDb only complains about the key if it's
+    // wrong
+    match client.get(request) {
+        Ok(body) => match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(response) => match OmdbError::from(response.error) {
+                OmdbError::InvalidApiKey => Err(Unauthorised),
+                _ => Ok(()),
+            },
+            Err(_) => Ok(()),
+        },
+        Err(RequestError::Status(status)) => Err(UnexpectedStatus(status)),
+        Err(why) => Err(RequestFailed(why)),
     }
 }
 
@@ -521,33 +2028,146 @@ fn api_key_format_acceptable(api_key: &str) -> bool {
     api_key.chars().all(|c| c.is_ascii_hexdigit())
 }
 
+/// Whether the search term is already an IMDb ID, like tt1049413
+pub fn is_imdb_id(search_term: &str) -> bool {
+    IMDB_ID_REGEX.is_match(search_term)
+}
+
 pub fn get_entry(api_key: &str, imdb_id: &str) -> Result<Entry, RequestError> {
-    let request = base_query(api_key).with_param("i", imdb_id);
-    send_request_deserialise(request)
+    let settings = request_settings();
+    let cache = settings.entry_cache.as_ref();
+    let keys = settings.api_keys.as_ref();
+    get_entry_with(&Minreq, api_key, imdb_id, false, cache, keys)
+}
+
+/// Like get_entry, but with OMDb's long plot if `full_plot` is set
+pub fn get_entry_plot(
+    api_key: &str,
+    imdb_id: &str,
+    full_plot: bool,
+) -> Result<Entry, RequestError> {
+    let settings = request_settings();
+    let cache = settings.entry_cache.as_ref();
+    let keys = settings.api_keys.as_ref();
+    get_entry_with(&Minreq, api_key, imdb_id, full_plot, cache, keys)
 }
 
-fn base_query(api_key: &str) -> Request {
-    minreq::get("https://www.omdbapi.com/")
-        .with_param("apikey", api_key)
-        // Lock to API version 1 and return type JSON in case this changes in
-        // future
-        .with_param("v", "1")
-        .with_param("r", "json")
+// Checks the cache (if there is one) before sending the request with the
+// client. Successful responses are written to the cache
+fn get_entry_with(
+    client: &dyn OmdbClient,
+    api_key: &str,
+    imdb_id: &str,
+    full_plot: bool,
+    cache: Option<&EntryCache>,
+    keys: Option<&ApiKeys>,
+) -> Result<Entry, RequestError> {
+    // Keep strange IDs (e.g. from --merge-stdin) out of file names
+    let cache = cache.filter(|_| is_imdb_id(imdb_id));
+    // Long plots are cached separately, as they're only sometimes wanted
+    let cache_key = if full_plot {
+        Cow::Owned(format!("{imdb_id}-full"))
+    } else {
+        Cow::Borrowed(imdb_id)
+    };
+    if let Some(cache) = cache {
+        match cache.get(&cache_key) {
+            Ok(Some(body)) => {
+                // Anything that's no longer understood is fetched again
+                if let Ok(entry) = deserialise_response(&body) {
+                    log::debug!("Using the saved details of {imdb_id}");
+                    return Ok(entry);
+                }
+            },
+            Ok(None) => {},
+            Err(why) => why.emit_unconditional(),
+        }
+    }
+
+    let (body, entry) = with_failover(keys, api_key, |api_key| {
+        let request = if full_plot {
+            base_query(api_key, &[("i", imdb_id), ("plot", "full")])
+        } else {
+            base_query(api_key, &[("i", imdb_id)])
+        };
+        let body = client.get(request)?;
+        let entry: Entry = deserialise_response(&body)?;
+        Ok((body, entry))
+    })?;
+    if let Some(cache) = cache {
+        if let Err(why) = cache.put(&cache_key, &body) {
+            why.emit_unconditional();
+        }
+    }
+    Ok(entry)
+}
+
+/// Gets the episodes of one season of a series
+pub fn get_season(
+    api_key: &str,
+    imdb_id: &str,
+    season: u16,
+) -> Result<Season, RequestError> {
+    get_season_with(
+        &Minreq,
+        api_key,
+        imdb_id,
+        season,
+        request_settings().api_keys.as_ref(),
+    )
+}
+
+fn get_season_with(
+    client: &dyn OmdbClient,
+    api_key: &str,
+    imdb_id: &str,
+    season: u16,
+    keys: Option<&ApiKeys>,
+) -> Result<Season, RequestError> {
+    with_failover(keys, api_key, |api_key| {
+        let season = season.to_string();
+        let request =
+            base_query(api_key, &[("i", imdb_id), ("Season", &season)]);
+        deserialise_response(&client.get(request)?)
+    })
+}
+
+// Values in the query should already be URL encoded where needed
+// Lock to API version 1 and return type JSON in case this changes in future
+const VERSION_QUERY: [(&str, &str); 2] = [("v", "1"), ("r", "json")];
+
+fn base_query(api_key: &str, query: &[(&str, &str)]) -> Request {
+    // The API key is left out so -vv output can be shared safely
+    log::debug!("Requesting https://www.omdbapi.com/?{}", shown_query(query));
+    VERSION_QUERY.iter().chain(query).fold(
+        get("https://www.omdbapi.com/").with_param("apikey", api_key),
+        |request, (key, value)| request.with_param(*key, *value),
+    )
 }
 
-// function is just a prettier, more explanatory name for
-// send_request_deserialise<SearchResults>
-fn send_omdb_search(request: Request) -> Result<SearchResults, RequestError> {
-    send_request_deserialise(request)
+// Everything in a request's query after the API key
+fn shown_query(query: &[(&str, &str)]) -> String {
+    VERSION_QUERY
+        .iter()
+        .chain(query)
+        .map(|(key, value)| format!("{key}={value}"))
+        .join("&")
 }
 
-fn send_request_deserialise<T>(request: Request) -> Result<T, RequestError>
+fn send_request_deserialise<T>(
+    client: &dyn OmdbClient,
+    request: Request,
+) -> Result<T, RequestError>
 where
     T: DeserialisableWithinOmdbResult + DeserializeOwned + Debug,
 {
-    let response = request.send()?;
-    let body = response.as_str()?;
+    deserialise_response(&client.get(request)?)
+}
 
+fn deserialise_response<T>(body: &str) -> Result<T, RequestError>
+where
+    T: DeserialisableWithinOmdbResult + DeserializeOwned + Debug,
+{
     serde_json::from_str::<OmdbResult<T>>(body)
         .map_err(|_| {
             // We re-attempt parsing to get a more useful error out of serde
@@ -573,64 +2193,473 @@ where
 trait DeserialisableWithinOmdbResult {}
 impl DeserialisableWithinOmdbResult for SearchResults {}
 impl DeserialisableWithinOmdbResult for Entry {}
+impl DeserialisableWithinOmdbResult for Season {}
 
 #[cfg(test)]
 mod unit_tests {
     use super::*;
+    use crate::filters::CURRENT_YEAR;
 
     #[test]
-    fn api_key_format() {
-        assert!(!api_key_format_acceptable("fizzbuzz"));
-        assert!(!api_key_format_acceptable("3q;mgh3w"));
-        assert!(!api_key_format_acceptable("foo"));
-        assert!(!api_key_format_acceptable("foobarbaz"));
+    fn user_agent_header() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
 
-        assert!(api_key_format_acceptable("123f3"));
-        assert!(api_key_format_acceptable("435adf312b"));
-        assert!(api_key_format_acceptable("13495632"));
-        assert!(api_key_format_acceptable("3a3d4e1f"));
+        // Pretend to be a server to see what headers are sent
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let headers = BufReader::new(&stream)
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            headers
+        });
+        get(format!("http://{addr}/")).send().unwrap();
+        let headers = server.join().unwrap();
+        let user_agent = format!("User-Agent: {DEFAULT_USER_AGENT}");
+        assert!(
+            headers.iter().any(|h| h.eq_ignore_ascii_case(&user_agent)),
+            "no user agent in {headers:?}"
+        );
     }
 
     #[test]
-    fn optional() {
-        #[derive(Debug, Deserialize)]
-        struct Maybe {
-            #[serde(deserialize_with = "de_option_parseable")]
-            just_maybe: Option<i32>,
-        }
+    fn proxy_used() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
 
-        let inputs = vec![
-            r#"{"just_maybe": "-12"}"#,
-            r#"{"just_maybe": "N/A"}"#,
-            r#"{"just_maybe": "123"}"#,
-        ];
-        let outputs = vec![Some(-12), None, Some(123)];
-        inputs
-            .into_iter()
-            .zip(outputs)
-            .for_each(|(input, expected)| {
-                let maybe = serde_json::from_str::<Maybe>(input).unwrap();
-                assert_eq!(maybe.just_maybe, expected);
-            });
+        // Pretend to be the proxy to see what's asked of it, then hang up
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut first_line = String::new();
+            BufReader::new(&stream).read_line(&mut first_line).unwrap();
+            first_line
+        });
+        let proxy = Proxy::new(addr.to_string()).unwrap();
+        let request = get_via("http://www.omdbapi.com/", Some(&proxy));
+        assert_ne!(request, get_via("http://www.omdbapi.com/", None));
+        let _ = request.send();
+        let first_line = server.join().unwrap();
+        assert!(
+            first_line.starts_with("CONNECT www.omdbapi.com:80 "),
+            "proxy was sent {first_line:?}"
+        );
     }
 
     #[test]
-    #[allow(dead_code)]
-    fn not_so_optional() {
-        #[derive(Debug, Deserialize)]
-        struct Definitely {
-            #[serde(deserialize_with = "de_parseable")]
-            surely: i32,
-        }
-        serde_json::from_str::<Definitely>(r#"{"surely": "N/A"}"#).unwrap_err();
+    fn timeout() {
+        use std::net::TcpListener;
+
+        // Accepts the connection but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            thread::sleep(Duration::from_secs(3));
+        });
+        let err = Minreq
+            .get(get(format!("http://{addr}/")).with_timeout(1))
+            .unwrap_err();
+        assert!(matches!(err, RequestError::Timeout), "got {err:?}");
+        assert!(!err.is_fatal());
     }
 
     #[test]
-    fn optional_comma_list() {
-        #[derive(Debug, Deserialize)]
-        struct MaybeCommaList {
-            #[serde(deserialize_with = "de_option_comma_list")]
-            just_maybe: Option<Vec<String>>,
+    fn imdb_id_detection() {
+        assert!(is_imdb_id("tt1049413"));
+        assert!(is_imdb_id("tt10872600"));
+
+        assert!(!is_imdb_id("up"));
+        assert!(!is_imdb_id("tt104941"));
+        assert!(!is_imdb_id("tt123456789"));
+        assert!(!is_imdb_id("TT1049413"));
+        assert!(!is_imdb_id("tt1049413 up"));
+        assert!(!is_imdb_id("https://www.imdb.com/title/tt1049413/"));
+    }
+
+    #[test]
+    fn entry_cache_hit() {
+        let mut dir = env::temp_dir();
+        dir.push(format!("imdb-id-{}-entry-cache", std::process::id()));
+        let cache = EntryCache::in_dir(dir.clone(), 1, false);
+
+        let up = |_| Ok(INPUTS[0].to_owned());
+        // Miss: fetched and written through
+        let entry =
+            get_entry_with(&up, "", "tt1049413", false, Some(&cache), None)
+                .unwrap();
+        assert_eq!(entry.title, "Up");
+
+        // Hit: no request is made
+        let unreachable = |_| panic!("entry should have come from the cache");
+        let entry = get_entry_with(
+            &unreachable,
+            "",
+            "tt1049413",
+            false,
+            Some(&cache),
+            None,
+        )
+        .unwrap();
+        assert_eq!(entry.title, "Up");
+
+        // --refresh skips the cache
+        let refreshing = EntryCache::in_dir(dir.clone(), 1, true);
+        let sent = AtomicUsize::new(0);
+        let counted = |_| {
+            sent.fetch_add(1, Ordering::Relaxed);
+            Ok(INPUTS[0].to_owned())
+        };
+        get_entry_with(
+            &counted,
+            "",
+            "tt1049413",
+            false,
+            Some(&refreshing),
+            None,
+        )
+        .unwrap();
+        assert_eq!(sent.into_inner(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn full_plot_param() {
+        let plain = base_query("", &[("i", "tt1049413")]);
+        let full = base_query("", &[("i", "tt1049413"), ("plot", "full")]);
+
+        let expecting = |expected: Request| {
+            move |request| {
+                assert_eq!(request, expected);
+                Ok(INPUTS[0].to_owned())
+            }
+        };
+        get_entry_with(&expecting(plain), "", "tt1049413", false, None, None)
+            .unwrap();
+        get_entry_with(&expecting(full), "", "tt1049413", true, None, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn season() {
+        // Breakout Kings, season 1 (trimmed to three episodes)
+        let body = r#"{"Title":"Breakout Kings","Season":"1","totalSeasons":"2","Episodes":[{"Title":"Pilot","Released":"2011-03-06","Episode":"1","imdbRating":"7.6","imdbID":"tt1784194"},{"Title":"Out of Sight","Released":"2011-03-13","Episode":"2","imdbRating":"7.6","imdbID":"tt1833092"},{"Title":"Round Trip","Released":"N/A","Episode":"3","imdbRating":"N/A","imdbID":"tt1846755"}],"Response":"True"}"#;
+        let request = base_query("", &[("i", "tt1590961"), ("Season", "1")]);
+        let client = |sent| {
+            assert_eq!(sent, request);
+            Ok(body.to_owned())
+        };
+        let season =
+            get_season_with(&client, "", "tt1590961", 1, None).unwrap();
+        assert_eq!(season.title, "Breakout Kings");
+        assert_eq!(season.season, 1);
+        assert_eq!(season.total_seasons, 2);
+        let episodes = season
+            .episodes
+            .iter()
+            .map(|episode| (episode.episode, episode.imdb_id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            episodes,
+            [(1, "tt1784194"), (2, "tt1833092"), (3, "tt1846755")],
+        );
+        assert_eq!(season.episodes[0].title, "Pilot");
+        assert_eq!(season.episodes[0].released.as_deref(), Some("2011-03-06"));
+        assert_eq!(season.episodes[0].rating, Some(7.6));
+        assert_eq!(season.episodes[2].released, None);
+        assert_eq!(season.episodes[2].rating, None);
+
+        // Asking for a season that doesn't exist
+        let not_found =
+            r#"{"Response":"False","Error":"Series or season not found!"}"#;
+        let client = |_| Ok(not_found.to_owned());
+        let err =
+            get_season_with(&client, "", "tt1590961", 9, None).unwrap_err();
+        assert!(matches!(err, RequestError::Omdb(OmdbError::NotFound)));
+    }
+
+    #[test]
+    fn serialise_entry() {
+        let up = serde_json::to_value(&DESERIALISED[0]).unwrap();
+        assert_eq!(up["title"], "Up");
+        assert_eq!(up["year"], "2009");
+        assert_eq!(up["imdb_id"], "tt1049413");
+        assert_eq!(up["media_type"], "movie");
+        assert_eq!(up["runtime"], 96);
+        assert_eq!(
+            up["genres"],
+            serde_json::json!(["Animation", "Adventure", "Comedy"]),
+        );
+        assert_eq!(
+            up["directors"],
+            serde_json::json!(["Pete Docter", "Bob Peterson"]),
+        );
+        assert_eq!(up["rating"], 8.2f32 as f64);
+        assert_eq!(up["votes"], 966_025);
+        assert_eq!(up["ratings"][1]["source"], "Rotten Tomatoes");
+        assert_eq!(up["ratings"][1]["value"], "98%");
+        assert_eq!(up["seasons"], serde_json::Value::Null);
+
+        // Genres come back out as they went in
+        let genres = up["genres"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|genre| genre.as_str().unwrap().parse::<Genre>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(genres, [Genre::Animation, Genre::Adventure, Genre::Comedy]);
+    }
+
+    #[test]
+    fn details_fall_back() {
+        let results = dummy_results("r", 6);
+        let details = get_details_with(&results, |imdb_id| match imdb_id {
+            "tt1049413" => Ok(DESERIALISED[0].clone()),
+            _ => Err(RequestError::Timeout),
+        });
+        assert_eq!(details.len(), 6);
+        for (sr, details) in results.iter().zip(&details) {
+            match details {
+                Details::Entry(entry) => assert_eq!(entry.imdb_id, "tt1049413"),
+                Details::Result(result) => {
+                    assert_eq!(result.imdb_id, sr.imdb_id)
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn genre_parsing() {
+        assert_eq!("comedy".parse(), Ok(Genre::Comedy));
+        assert_eq!("Sci-Fi".parse(), Ok(Genre::SciFi));
+        assert_eq!(" reality-tv ".parse(), Ok(Genre::RealityTv));
+        assert_eq!(
+            "Anime".parse::<Genre>(),
+            Err(GenreParseError("Anime".into())),
+        );
+        let err = "Anime".parse::<Genre>().unwrap_err().to_string();
+        assert!(err.starts_with(
+            "unknown genre \"Anime\", expected one of: Action, Adventure,"
+        ));
+        assert_eq!(Genre::FilmNoir.to_string(), "Film-Noir");
+        assert!(Genre::Other("Anime".into()) == *"anime");
+    }
+
+    #[test]
+    fn ratings() {
+        let up = &DESERIALISED[0];
+        let sources = up
+            .ratings
+            .iter()
+            .map(|rating| (rating.source.clone(), rating.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            [
+                (RatingSource::Imdb, "8.2/10"),
+                (RatingSource::RottenTomatoes, "98%"),
+                (RatingSource::Metacritic, "88/100"),
+            ]
+        );
+
+        let other = serde_json::from_str::<Rating>(
+            r#"{"Source":"Letterboxd","Value":"4.1/5"}"#,
+        )
+        .unwrap();
+        assert_eq!(other.source, RatingSource::Other("Letterboxd".into()));
+    }
+
+    #[test]
+    fn votes() {
+        #[derive(Debug, Deserialize)]
+        struct Votes {
+            #[serde(deserialize_with = "de_option_votes")]
+            votes: Option<u32>,
+        }
+        let parse = |json| serde_json::from_str::<Votes>(json);
+        assert_eq!(
+            parse(r#"{"votes": "966,025"}"#).unwrap().votes,
+            Some(966_025)
+        );
+        assert_eq!(
+            parse(r#"{"votes": "15,196"}"#).unwrap().votes,
+            Some(15_196)
+        );
+        assert_eq!(parse(r#"{"votes": "N/A"}"#).unwrap().votes, None);
+        parse(r#"{"votes": "lots"}"#).unwrap_err();
+
+        assert_eq!(DESERIALISED[0].votes, Some(966_025));
+    }
+
+    #[test]
+    fn awards() {
+        let awards = |oscars_won, wins, nominations| Awards {
+            oscars_won,
+            wins,
+            nominations,
+        };
+        assert_eq!(DESERIALISED[0].awards, Some(awards(2, 79, 87)));
+        assert_eq!(DESERIALISED[1].awards, Some(awards(3, 134, 199)));
+        assert_eq!(DESERIALISED[3].awards, None);
+        assert_eq!(
+            "Nominated for 1 Oscar. 1 win & 4 nominations total".parse(),
+            Ok(awards(0, 1, 4)),
+        );
+        assert_eq!("3 nominations".parse(), Ok(awards(0, 0, 3)));
+        assert_eq!(
+            "N/A".parse::<Awards>(),
+            Err(AwardsParseError("N/A".into()))
+        );
+    }
+
+    #[test]
+    fn metascore() {
+        assert_eq!(DESERIALISED[0].metascore, Some(88));
+        assert_eq!(DESERIALISED[3].metascore, None);
+
+        #[derive(Debug, Deserialize)]
+        struct Metascore {
+            #[serde(deserialize_with = "de_option_metascore")]
+            metascore: Option<u8>,
+        }
+        let parse = |json| serde_json::from_str::<Metascore>(json);
+        assert_eq!(
+            parse(r#"{"metascore": "100"}"#).unwrap().metascore,
+            Some(100)
+        );
+        parse(r#"{"metascore": "101"}"#).unwrap_err();
+        parse(r#"{"metascore": "-1"}"#).unwrap_err();
+    }
+
+    #[test]
+    fn currency() {
+        assert_eq!(parse_currency("$293,004,164"), Some(293_004_164));
+        assert_eq!(parse_currency("$0"), Some(0));
+        assert_eq!(parse_currency("N/A"), None);
+        assert_eq!(parse_currency("293,004,164"), None);
+        assert_eq!(parse_currency("$"), None);
+        assert_eq!(parse_currency("$+5"), None);
+
+        #[derive(Debug, Deserialize)]
+        struct BoxOffice {
+            #[serde(deserialize_with = "de_option_currency")]
+            box_office: Option<u64>,
+        }
+        let parse = |json| serde_json::from_str::<BoxOffice>(json);
+        assert_eq!(parse(r#"{"box_office": "N/A"}"#).unwrap().box_office, None);
+        parse(r#"{"box_office": "lots"}"#).unwrap_err();
+    }
+
+    #[test]
+    fn released() {
+        #[derive(Debug, Deserialize)]
+        struct Released {
+            #[serde(deserialize_with = "de_option_released")]
+            released: Option<Date>,
+        }
+        let parse = |json| serde_json::from_str::<Released>(json);
+        assert_eq!(
+            parse(r#"{"released": "29 May 2009"}"#).unwrap().released,
+            Some(Date::from_calendar_date(2009, time::Month::May, 29).unwrap())
+        );
+        assert_eq!(parse(r#"{"released": "N/A"}"#).unwrap().released, None);
+        parse(r#"{"released": "2009-05-29"}"#).unwrap_err();
+        parse(r#"{"released": "31 Feb 2009"}"#).unwrap_err();
+
+        let up = serde_json::to_value(&DESERIALISED[0]).unwrap();
+        assert_eq!(up["released"], "2009-05-29");
+    }
+
+    #[test]
+    fn runtime() {
+        let runtime = "96 min".parse::<Runtime>().unwrap();
+        assert_eq!(runtime, Runtime(Duration::from_secs(96 * 60)));
+        assert_eq!(runtime.to_string(), "96 minutes");
+
+        assert!(matches!(
+            "abc min".parse::<Runtime>(),
+            Err(RuntimeParseError::InvalidInt(_)),
+        ));
+        assert!(matches!(
+            "96".parse::<Runtime>(),
+            Err(RuntimeParseError::NoUnit(_)),
+        ));
+
+        #[derive(Debug, Deserialize)]
+        struct Runtimes {
+            #[serde(deserialize_with = "de_option_parseable")]
+            runtime: Option<Runtime>,
+        }
+        let parse = |json| serde_json::from_str::<Runtimes>(json);
+        assert_eq!(
+            parse(r#"{"runtime": "96 min"}"#).unwrap().runtime,
+            Some(runtime)
+        );
+        assert_eq!(parse(r#"{"runtime": "N/A"}"#).unwrap().runtime, None);
+        parse(r#"{"runtime": "abc min"}"#).unwrap_err();
+    }
+
+    #[test]
+    fn api_key_format() {
+        assert!(!api_key_format_acceptable("fizzbuzz"));
+        assert!(!api_key_format_acceptable("3q;mgh3w"));
+        assert!(!api_key_format_acceptable("foo"));
+        assert!(!api_key_format_acceptable("foobarbaz"));
+
+        assert!(api_key_format_acceptable("123f3"));
+        assert!(api_key_format_acceptable("435adf312b"));
+        assert!(api_key_format_acceptable("13495632"));
+        assert!(api_key_format_acceptable("3a3d4e1f"));
+    }
+
+    #[test]
+    fn optional() {
+        #[derive(Debug, Deserialize)]
+        struct Maybe {
+            #[serde(deserialize_with = "de_option_parseable")]
+            just_maybe: Option<i32>,
+        }
+
+        let inputs = vec![
+            r#"{"just_maybe": "-12"}"#,
+            r#"{"just_maybe": "N/A"}"#,
+            r#"{"just_maybe": "123"}"#,
+        ];
+        let outputs = vec![Some(-12), None, Some(123)];
+        inputs
+            .into_iter()
+            .zip(outputs)
+            .for_each(|(input, expected)| {
+                let maybe = serde_json::from_str::<Maybe>(input).unwrap();
+                assert_eq!(maybe.just_maybe, expected);
+            });
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn not_so_optional() {
+        #[derive(Debug, Deserialize)]
+        struct Definitely {
+            #[serde(deserialize_with = "de_parseable")]
+            surely: i32,
+        }
+        serde_json::from_str::<Definitely>(r#"{"surely": "N/A"}"#).unwrap_err();
+    }
+
+    #[test]
+    fn optional_comma_list() {
+        #[derive(Debug, Deserialize)]
+        struct MaybeCommaList {
+            #[serde(deserialize_with = "de_option_comma_list")]
+            just_maybe: Option<Vec<String>>,
         }
 
         let inputs = vec![
@@ -670,6 +2699,37 @@ mod unit_tests {
         serde_json::from_str::<DefinitelyCommaList>("N/A").unwrap_err();
     }
 
+    #[test]
+    fn search_result_poster() {
+        let with_poster = r#"{"Title":"Up","Year":"2009","imdbID":"tt1049413","Type":"movie","Poster":"https://m.media-amazon.com/images/M/MV5BMTk3NDE2NzI4NF5BMl5BanBnXkFtZTgwNzE1MzEyMTE@._V1_SX300.jpg"}"#;
+        let sr = serde_json::from_str::<SearchResult>(with_poster).unwrap();
+        assert_eq!(
+            sr.poster.as_deref(),
+            Some("https://m.media-amazon.com/images/M/MV5BMTk3NDE2NzI4NF5BMl5BanBnXkFtZTgwNzE1MzEyMTE@._V1_SX300.jpg"),
+        );
+        let json = serde_json::to_value(&sr).unwrap();
+        assert_eq!(json["poster"], sr.poster.unwrap().as_str());
+
+        let without_poster = r#"{"Title":"Up","Year":"2009","imdbID":"tt1049413","Type":"movie","Poster":"N/A"}"#;
+        let sr = serde_json::from_str::<SearchResult>(without_poster).unwrap();
+        assert_eq!(sr.poster, None);
+        // Human output is unchanged
+        assert_eq!(sr.to_string(), "Up (movie, 2009)");
+    }
+
+    #[test]
+    fn search_result_years() {
+        let year_of = |year: &str| {
+            let json = format!(
+                r#"{{"Title":"Breakout Kings","Year":"{year}","imdbID":"tt1590961","Type":"series","Poster":"N/A"}}"#
+            );
+            serde_json::from_str::<SearchResult>(&json).unwrap().year.0
+        };
+        assert_eq!(year_of("2011"), 2011..=2011);
+        assert_eq!(year_of("2011–2012"), 2011..=2012);
+        assert_eq!(year_of("2011–"), 2011..=*CURRENT_YEAR);
+    }
+
     const INPUTS: [&str; 4] = [
         // Up
         r#"{"Title":"Up","Year":"2009","Rated":"PG","Released":"29 May 2009","Runtime":"96 min","Genre":"Animation, Adventure, Comedy","Director":"Pete Docter, Bob Peterson","Writer":"Pete Docter, Bob Peterson, Tom McCarthy","Actors":"Edward Asner, Jordan Nagai, John Ratzenberger","Plot":"78-year-old Carl Fredricksen travels to Paradise Falls in his house equipped with balloons, inadvertently taking a young stowaway.","Language":"English","Country":"United States","Awards":"Won 2 Oscars. 79 wins & 87 nominations total","Poster":"https://m.media-amazon.com/images/M/MV5BMTk3NDE2NzI4NF5BMl5BanBnXkFtZTgwNzE1MzEyMTE@._V1_SX300.jpg","Ratings":[{"Source":"Internet Movie Database","Value":"8.2/10"},{"Source":"Rotten Tomatoes","Value":"98%"},{"Source":"Metacritic","Value":"88/100"}],"Metascore":"88","imdbRating":"8.2","imdbVotes":"966,025","imdbID":"tt1049413","Type":"movie","DVD":"21 Nov 2015","BoxOffice":"$293,004,164","Production":"Pixar Animation Studios","Website":"N/A","Response":"True"}"#,
@@ -681,6 +2741,1105 @@ mod unit_tests {
         r#"{"Title":"Breakout Kings","Year":"2011–2012","Rated":"TV-14","Released":"06 Mar 2011","Runtime":"43 min","Genre":"Crime, Drama, Thriller","Director":"N/A","Writer":"Matt Olmstead, Nick Santora","Actors":"Domenick Lombardozzi, Brooke Nevin, Malcolm Goodwin","Plot":"A squad of U.S. marshals team up with cons (former fugitives) to work together on tracking down prison escapees in exchange for getting time off their sentences.","Language":"English","Country":"United States","Awards":"N/A","Poster":"https://m.media-amazon.com/images/M/MV5BMTcyNzUwNjMwM15BMl5BanBnXkFtZTcwOTgxNjk0Nw@@._V1_SX300.jpg","Ratings":[{"Source":"Internet Movie Database","Value":"7.3/10"}],"Metascore":"N/A","imdbRating":"7.3","imdbVotes":"15,196","imdbID":"tt1590961","Type":"series","totalSeasons":"2","Response":"True"}"#,
     ];
 
+    fn dummy_results(prefix: &str, count: usize) -> Vec<SearchResult> {
+        (0..count)
+            .map(|rank| SearchResult {
+                title: format!("{prefix}{rank}"),
+                imdb_id: format!("tt{prefix}{rank}"),
+                media_type: MediaType::MOVIE,
+                year: Year(2000..=2000),
+                poster: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_unequal_lengths() {
+        let sets = vec![
+            dummy_results("a", 1),
+            dummy_results("b", 3),
+            dummy_results("c", 5),
+        ];
+        let merged = merge_result_sets(sets)
+            .into_iter()
+            .map(|sr| sr.title)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            merged,
+            ["a0", "b0", "c0", "b1", "c1", "b2", "c2", "c3", "c4"],
+        );
+    }
+
+    #[test]
+    fn stitch_pages() {
+        let page = |entries: &[(&str, &str)]| {
+            let entries = entries
+                .iter()
+                .map(|(title, id)| {
+                    format!(
+                        r#"{{"Title":"{title}","Year":"2000","imdbID":"{id}","Type":"movie","Poster":"N/A"}}"#
+                    )
+                })
+                .join(",");
+            serde_json::from_str::<SearchResults>(&format!(
+                r#"{{"Search":[{entries}],"totalResults":"4","Response":"True"}}"#
+            ))
+            .unwrap()
+        };
+        let mut set = Vec::new();
+        assert!(!add_page(&mut set, page(&[("a", "tt1"), ("b", "tt2")])));
+        // OMDb sometimes repeats results across pages
+        assert!(add_page(&mut set, page(&[("b", "tt2"), ("c", "tt3")])));
+
+        let other = dummy_results("x", 2);
+        let merged = merge_result_sets(vec![set, other])
+            .into_iter()
+            .map(|sr| sr.title)
+            .collect::<Vec<_>>();
+        assert_eq!(merged, ["a", "x0", "b", "x1", "c"]);
+    }
+
+    #[test]
+    fn merge_removes_duplicates() {
+        let result = |title: &str, id: &str, year, media_type| SearchResult {
+            title: title.into(),
+            imdb_id: id.into(),
+            media_type,
+            year: Year(year..=year),
+            poster: None,
+        };
+        let movies = vec![
+            result("Up", "tt1049413", 2009, MediaType::MOVIE),
+            result(
+                "Dug's Special Mission",
+                "tt1523939",
+                2009,
+                MediaType::MOVIE,
+            ),
+            result("1917", "tt8579674", 2019, MediaType::MOVIE),
+        ];
+        let others = vec![
+            // Seen in the other search
+            result("Up", "tt1049413", 2009, MediaType::MOVIE),
+            // Same title and year under another ID
+            result(
+                "Dug's Special Mission",
+                "tt9999999",
+                2009,
+                MediaType::MOVIE,
+            ),
+            // Same title, different year
+            result("1917", "tt0008570", 1917, MediaType::MOVIE),
+            // Same title and year, but a game rather than the movie
+            result("Up", "tt1380798", 2009, MediaType::GAME),
+        ];
+        let merged = merge_result_sets(vec![movies, others])
+            .into_iter()
+            .map(|sr| sr.imdb_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            merged,
+            [
+                "tt1049413",
+                "tt1523939",
+                "tt8579674",
+                "tt0008570",
+                "tt1380798"
+            ],
+        );
+    }
+
+    #[test]
+    fn bundle_filters_results() {
+        let results = || {
+            vec![
+                SearchResult {
+                    title: "Black Mirror".into(),
+                    imdb_id: "tt2085059".into(),
+                    media_type: MediaType::SERIES,
+                    year: Year(2011..=2019),
+                    poster: None,
+                },
+                SearchResult {
+                    title: "Up".into(),
+                    imdb_id: "tt1049413".into(),
+                    media_type: MediaType::MOVIE,
+                    year: Year(2009..=2009),
+                    poster: None,
+                },
+                SearchResult {
+                    title: "1917".into(),
+                    imdb_id: "tt8579674".into(),
+                    media_type: MediaType::MOVIE,
+                    year: Year(2019..=2019),
+                    poster: None,
+                },
+            ]
+        };
+        let ids = |results: Vec<SearchResult>| {
+            results.into_iter().map(|sr| sr.imdb_id).collect::<Vec<_>>()
+        };
+
+        // Black Mirror only overlaps at the end of its run
+        let filters = Filters {
+            types: MediaType::ALL,
+            years: vec![Year(2019..=2020)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        assert_eq!(
+            ids(bundle.apply_filters(results())),
+            ["tt2085059", "tt8579674"]
+        );
+
+        let filters = Filters {
+            types: MediaType::MOVIE,
+            years: vec![Year(2009..=2019)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        assert_eq!(
+            ids(bundle.apply_filters(results())),
+            ["tt1049413", "tt8579674"]
+        );
+
+        let filters = Filters::default();
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        assert_eq!(ids(bundle.apply_filters(results())).len(), 3);
+    }
+
+    #[test]
+    fn max_requests_truncates_params() {
+        let filters = Filters {
+            types: MediaType::ALL,
+            years: vec![Year(2000..=2009)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new("", "", &filters, 3);
+        assert_eq!(bundle.params.len(), 3);
+        let bundle = RequestBundle::new("", "", &filters, 20);
+        assert_eq!(bundle.params.len(), 10);
+
+        // One request per year and type
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: vec![Year(2000..=2002)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new("", "", &filters, 4);
+        assert_eq!(bundle.params.len(), 4);
+        assert!(bundle.params.iter().all(|p| p.year.is_some()));
+        // The limit carries over to searches made from this one
+        assert_eq!(bundle.search_again("up").params.len(), 4);
+    }
+
+    #[test]
+    fn dry_run_urls() {
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: vec![Year(2019..=2020)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new("secret", "star wars", &filters, 10);
+        let base = "https://www.omdbapi.com/?apikey=********&v=1&r=json";
+        assert_eq!(
+            bundle.dry_run(),
+            [
+                format!("{base}&s=star%20wars&type=movie&y=2019"),
+                format!("{base}&s=star%20wars&type=series&y=2019"),
+                format!("{base}&s=star%20wars&type=movie&y=2020"),
+                format!("{base}&s=star%20wars&type=series&y=2020"),
+            ],
+        );
+        // Without any filters it's just the one search
+        let unfiltered = Filters::default();
+        let bundle = RequestBundle::new("secret", "up", &unfiltered, 10);
+        assert_eq!(bundle.dry_run(), [format!("{base}&s=up")]);
+    }
+
+    #[test]
+    fn concurrent_requests_keep_order() {
+        let filters = Filters {
+            types: MediaType::ALL,
+            years: vec![Year(2000..=2005)],
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let searches = bundle.params.len();
+        let page_of = |index: usize, page: u32| SearchResults {
+            entries: dummy_results(&format!("{index}p{page}r"), 10),
+            total_results: 20,
+        };
+        let known = (0..searches)
+            .cartesian_product(1..=2)
+            .map(|(index, page)| {
+                (
+                    (index, page),
+                    bundle.request("", &bundle.params[index], page),
+                )
+            })
+            .collect::<Vec<_>>();
+        let send = |request: Request| {
+            let &((index, page), _) = known
+                .iter()
+                .find(|(_, known)| *known == request)
+                .expect("request for an unknown search");
+            // Earlier searches answer last
+            thread::sleep(Duration::from_millis((searches - index) as u64 * 5));
+            Ok(page_of(index, page))
+        };
+        let results = bundle
+            .get_pages(1, usize::MAX, 100, false, send)
+            .unwrap()
+            .results;
+
+        // What sending them one at a time gives
+        let sequential = (0..searches)
+            .map(|index| {
+                let mut set = Vec::new();
+                add_page(&mut set, page_of(index, 1));
+                add_page(&mut set, page_of(index, 2));
+                set
+            })
+            .collect();
+        let ids = |results: Vec<SearchResult>| {
+            results.into_iter().map(|sr| sr.imdb_id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(results), ids(merge_result_sets(sequential)));
+        assert_eq!(bundle.next_page(), 3);
+    }
+
+    #[test]
+    fn total_across_pages() {
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: Vec::new(),
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        // Movies have 3 pages of results, series only 1
+        let totals = [25, 8];
+        let known = (0..2)
+            .cartesian_product(1..=3)
+            .map(|(index, page)| {
+                (
+                    (index, page),
+                    bundle.request("", &bundle.params[index], page),
+                )
+            })
+            .collect::<Vec<_>>();
+        let send = |request: Request| {
+            let &((index, page), _) = known
+                .iter()
+                .find(|(_, known)| *known == request)
+                .expect("request for an unknown search");
+            let read = (page as usize - 1) * 10;
+            let count = (totals[index] - read).min(10);
+            Ok(SearchResults {
+                entries: dummy_results(&format!("{index}p{page}r"), count),
+                total_results: totals[index] as u32,
+            })
+        };
+
+        // Only the first page is needed, but the total is still known
+        let matches = bundle.get_pages(1, 10, 10, false, send).unwrap();
+        assert_eq!(matches.results.len(), 18);
+        assert_eq!(matches.total, 33);
+
+        let matches = bundle.get_pages(1, usize::MAX, 10, false, send).unwrap();
+        assert_eq!(matches.results.len(), 33);
+        assert_eq!(matches.total, 33);
+    }
+
+    #[test]
+    fn limit_reached() {
+        let body = r#"{"Response":"False","Error":"Request limit reached!"}"#;
+        let err = deserialise_response::<Entry>(body).unwrap_err();
+        assert!(
+            matches!(err, RequestError::Omdb(OmdbError::RequestLimitReached)),
+            "got {err:?}"
+        );
+        assert!(err.is_fatal());
+    }
+
+    #[test]
+    fn omdb_error_messages() {
+        use OmdbError::*;
+        let known = [
+            ("Movie not found!", NotFound),
+            ("Series not found!", NotFound),
+            ("Series or season not found!", NotFound),
+            ("Incorrect IMDb ID.", NotFound),
+            ("Request limit reached!", RequestLimitReached),
+            ("Too many results.", TooManyResults),
+            ("Invalid API key!", InvalidApiKey),
+            ("No API key provided.", InvalidApiKey),
+            ("Error getting data.", Other("Error getting data.".into())),
+        ];
+        for (message, expected) in known {
+            let body = format!(r#"{{"Response":"False","Error":"{message}"}}"#);
+            match deserialise_response::<Entry>(&body).unwrap_err() {
+                RequestError::Omdb(err) => assert_eq!(err, expected),
+                other => panic!("got {other:?} for {message:?}"),
+            }
+        }
+
+        assert!(!NotFound.is_fatal());
+        assert!(!TooManyResults.is_fatal());
+        assert!(RequestLimitReached.is_fatal());
+        assert!(InvalidApiKey.is_fatal());
+        assert!(RequestError::Omdb(InvalidApiKey).is_fatal());
+        assert!(!RequestError::Omdb(NotFound).is_fatal());
+    }
+
+    #[test]
+    fn over_budget() {
+        let quota = |enforced| Quota {
+            count: Mutex::new(RequestCount {
+                date: "2024-03-01".into(),
+                count: 2,
+            }),
+            budget: 3,
+            enforced,
+            save: false,
+        };
+
+        let enforced = quota(true);
+        assert!(enforced.take_on("2024-03-01").is_ok());
+        let err = enforced.take_on("2024-03-01").unwrap_err();
+        assert!(matches!(err, RequestError::OverBudget(3)), "got {err:?}");
+        assert!(err.is_fatal());
+        // The next day has a fresh budget
+        assert!(enforced.take_on("2024-03-02").is_ok());
+
+        // --ignore-quota
+        let ignored = quota(false);
+        for _ in 0..3 {
+            ignored.take_on("2024-03-01").unwrap();
+        }
+        assert_eq!(ignored.count.lock().unwrap().count, 5);
+    }
+
+    #[test]
+    fn api_key_failover() {
+        let keys = ApiKeys::new(vec!["second".into(), "third".into()]);
+        let filters = Filters::default();
+        let mut bundle = RequestBundle::new(
+            "first",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        bundle.keys = Some(&keys);
+        let params = &bundle.params[0];
+        let known = ["first", "second", "third"]
+            .into_iter()
+            .cartesian_product(1..=2)
+            .map(|(key, page)| (key, page, bundle.request(key, params, page)))
+            .collect::<Vec<_>>();
+        let sent = Mutex::new(Vec::new());
+        // The first key has reached its limit
+        let send = |request: Request| {
+            let &(key, page, _) = known
+                .iter()
+                .find(|(_, _, known)| *known == request)
+                .expect("request with an unknown key");
+            sent.lock().unwrap().push((key, page));
+            match key {
+                "first" => {
+                    Err(RequestError::Omdb(OmdbError::RequestLimitReached))
+                },
+                _ => Ok(SearchResults {
+                    entries: dummy_results(&format!("{key}p{page}r"), 10),
+                    total_results: 20,
+                }),
+            }
+        };
+
+        let matches = bundle.get_pages(1, 20, 10, false, send).unwrap();
+        assert_eq!(matches.results.len(), 20);
+        assert_eq!(matches.results[0].imdb_id, "ttsecondp1r0");
+        // Once a key is over the limit, it isn't tried again
+        assert_eq!(
+            *sent.lock().unwrap(),
+            [("first", 1), ("second", 1), ("second", 2)]
+        );
+
+        // Entries fail over the same way
+        let tried = Mutex::new(Vec::new());
+        let client = |request| {
+            let mut tried = tried.lock().unwrap();
+            tried.push(request);
+            match tried.len() {
+                1 => Err(RequestError::Omdb(OmdbError::RequestLimitReached)),
+                _ => Ok(INPUTS[0].to_owned()),
+            }
+        };
+        let entry = get_entry_with(
+            &client,
+            "first",
+            "tt1049413",
+            false,
+            None,
+            Some(&keys),
+        );
+        assert_eq!(entry.unwrap().title, "Up");
+        // "first" is already known to be over the limit, so "second" is
+        // tried first, then "third" after it says it's over the limit too
+        let with_key = |key| base_query(key, &[("i", "tt1049413")]);
+        assert_eq!(
+            tried.into_inner().unwrap(),
+            [with_key("second"), with_key("third")]
+        );
+
+        // Nothing can be done once every key has reached its limit
+        let client =
+            |_| Err(RequestError::Omdb(OmdbError::RequestLimitReached));
+        let err = get_entry_with(
+            &client,
+            "first",
+            "tt1049413",
+            false,
+            None,
+            Some(&keys),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, RequestError::Omdb(OmdbError::RequestLimitReached)),
+            "got {err:?}"
+        );
+    }
+
+    #[test]
+    fn batch_of_three() {
+        let filters = Filters::default();
+        let search_terms = ["Up", "Nothing at all", "1917"].map(String::from);
+        let known = search_terms
+            .iter()
+            .map(|search_term| {
+                let bundle = RequestBundle::new(
+                    "",
+                    search_term,
+                    &filters,
+                    DEFAULT_MAX_REQUESTS_PER_SEARCH,
+                );
+                (
+                    bundle.request("", &bundle.params[0], 1),
+                    search_term.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let send = |request: Request| {
+            let (_, search_term) = known
+                .iter()
+                .find(|(known, _)| *known == request)
+                .expect("request for an unknown title");
+            match search_term.as_str() {
+                "Nothing at all" => {
+                    Err(RequestError::Omdb(OmdbError::NotFound))
+                },
+                title => Ok(SearchResults {
+                    entries: dummy_results(title, 3),
+                    total_results: 3,
+                }),
+            }
+        };
+
+        let results = batch_search_with(
+            "",
+            &search_terms,
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+            send,
+        )
+        .unwrap();
+        let found = results
+            .iter()
+            .map(|br| {
+                (
+                    br.search_term.as_str(),
+                    br.result.as_ref().map(|sr| sr.imdb_id.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                ("Up", Some("ttUp0")),
+                ("Nothing at all", None),
+                ("1917", Some("tt19170")),
+            ]
+        );
+    }
+
+    #[test]
+    fn all_requests_failed() {
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            years: Vec::new(),
+            ..Default::default()
+        };
+        let bundle = RequestBundle::new(
+            "",
+            "",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let warning = |_| {
+            let err = serde_json::from_str::<SearchResults>("{}").unwrap_err();
+            Err(RequestError::Deserialisation(err, "{}".into()))
+        };
+        let err = bundle.get_pages(1, 10, 10, false, warning).unwrap_err();
+        assert!(
+            matches!(err, RequestError::AllRequestsFailed),
+            "got {err:?}"
+        );
+
+        // Not found still wins if any search says so
+        let first = std::sync::atomic::AtomicBool::new(true);
+        let mixed = |request| {
+            if first.swap(false, Ordering::Relaxed) {
+                Err(RequestError::Omdb(OmdbError::NotFound))
+            } else {
+                warning(request)
+            }
+        };
+        let err = bundle.get_pages(1, 10, 10, false, mixed).unwrap_err();
+        assert!(matches!(err, RequestError::Omdb(_)), "got {err:?}");
+    }
+
+    // Gives canned JSON for each request it knows about, like OMDb would
+    struct FakeOmdb(Vec<(Request, String)>);
+
+    impl OmdbClient for FakeOmdb {
+        fn get(&self, request: Request) -> Result<String, RequestError> {
+            let (_, body) = self
+                .0
+                .iter()
+                .find(|(known, _)| *known == request)
+                .expect("request OMDb wasn't expecting");
+            Ok(body.clone())
+        }
+    }
+
+    // A page of search results, as OMDb gives them
+    fn search_page(ids: &[&str], total: usize) -> String {
+        let results = ids
+            .iter()
+            .map(|id| {
+                format!(
+                    r#"{{"Title":"{id}","Year":"2000","imdbID":"{id}","Type":"movie","Poster":"N/A"}}"#
+                )
+            })
+            .join(",");
+        format!(
+            r#"{{"Search":[{results}],"totalResults":"{total}","Response":"True"}}"#
+        )
+    }
+
+    const NOT_FOUND: &str =
+        r#"{"Response":"False","Error":"Movie not found!"}"#;
+    const INVALID_KEY: &str =
+        r#"{"Response":"False","Error":"Invalid API key!"}"#;
+
+    #[test]
+    fn fake_pagination() {
+        let filters = Filters::default();
+        let requests = RequestBundle::new(
+            "",
+            "up",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let request = |page| requests.request("", &requests.params[0], page);
+        let first_page = (1..=10).map(|id| format!("tt{id:07}")).collect_vec();
+        let first_page = first_page.iter().map(String::as_str).collect_vec();
+        // The second page repeats one of the first page's results
+        let second_page = [
+            "tt0000011",
+            "tt0000012",
+            "tt0000005",
+            "tt0000013",
+            "tt0000014",
+        ];
+        let omdb = FakeOmdb(vec![
+            (request(1), search_page(&first_page, 15)),
+            (request(2), search_page(&second_page, 15)),
+        ]);
+
+        let progress = Mutex::new(Vec::new());
+        let record = |made, most| progress.lock().unwrap().push((made, most));
+        let bundle = RequestBundle::with_client(
+            &omdb,
+            "",
+            "up",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        )
+        .with_progress(&record);
+        let matches = bundle.get_results(15, false).unwrap();
+        assert_eq!(matches.total, 15);
+        let most = DEFAULT_MAX_REQUESTS_PER_SEARCH;
+        assert_eq!(*progress.lock().unwrap(), [(1, most), (2, most)]);
+        let ids = matches
+            .results
+            .iter()
+            .map(|sr| sr.imdb_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), 14);
+        assert_eq!(ids[..10], first_page);
+        assert_eq!(
+            ids[10..],
+            ["tt0000011", "tt0000012", "tt0000013", "tt0000014"]
+        );
+        assert_eq!(bundle.next_page(), 3);
+    }
+
+    #[test]
+    fn fake_not_found_and_fatal() {
+        let filters = Filters {
+            types: MediaType::MOVIE | MediaType::SERIES,
+            ..Default::default()
+        };
+        let requests = RequestBundle::new(
+            "",
+            "up",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let [movies, series] = [0, 1]
+            .map(|index| requests.request("", &requests.params[index], 1));
+        // Made for suggestions when neither search finds anything
+        let unfiltered = Filters::default();
+        let loosened = RequestBundle::new(
+            "",
+            "up",
+            &unfiltered,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let loosened = loosened.request("", &loosened.params[0], 1);
+        let search = |movies_body: &str, series_body: &str| {
+            let omdb = FakeOmdb(vec![
+                (movies.clone(), movies_body.to_owned()),
+                (series.clone(), series_body.to_owned()),
+                (loosened.clone(), NOT_FOUND.to_owned()),
+            ]);
+            RequestBundle::with_client(
+                &omdb,
+                "",
+                "up",
+                &filters,
+                DEFAULT_MAX_REQUESTS_PER_SEARCH,
+            )
+            .get_results(10, false)
+        };
+
+        // Only one of the searches finding nothing is fine
+        let found = search(NOT_FOUND, &search_page(&["tt0000001"], 1));
+        assert_eq!(found.unwrap().results[0].imdb_id, "tt0000001");
+
+        // Both finding nothing isn't
+        let err = search(NOT_FOUND, NOT_FOUND).unwrap_err();
+        assert!(
+            matches!(&err, RequestError::Omdb(OmdbError::NotFound)),
+            "got {err:?}"
+        );
+
+        // Other errors from OMDb stop the search, even if the other search
+        // was fine
+        let err = search(INVALID_KEY, &search_page(&["tt0000001"], 1));
+        let err = err.unwrap_err();
+        assert!(err.is_fatal());
+        assert!(
+            matches!(&err, RequestError::Omdb(OmdbError::InvalidApiKey)),
+            "got {err:?}"
+        );
+    }
+
+    #[test]
+    fn did_you_mean() {
+        let filters = Filters {
+            types: MediaType::SERIES,
+            years: vec![Year(2000..=2000)],
+            ..Default::default()
+        };
+        let filtered = RequestBundle::new(
+            "",
+            "up",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let unfiltered = Filters::default();
+        let loosened = RequestBundle::new(
+            "",
+            "up",
+            &unfiltered,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        let omdb = FakeOmdb(vec![
+            (
+                filtered.request("", &filtered.params[0], 1),
+                NOT_FOUND.to_owned(),
+            ),
+            (
+                loosened.request("", &loosened.params[0], 1),
+                search_page(&["tt1", "tt2", "tt3", "tt4"], 4),
+            ),
+        ]);
+        let bundle = RequestBundle::with_client(
+            &omdb,
+            "",
+            "up",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        // Still nothing found, but some suggestions to go with it
+        let err = bundle.get_results(10, false).unwrap_err();
+        assert!(matches!(err, RequestError::Omdb(OmdbError::NotFound)));
+        let suggestions = bundle
+            .suggestions()
+            .into_iter()
+            .map(|sr| sr.imdb_id)
+            .collect::<Vec<_>>();
+        assert_eq!(suggestions, ["tt1", "tt2", "tt3"]);
+
+        // Without filters there's nothing to loosen, so no more requests
+        let omdb = FakeOmdb(Vec::new());
+        let bundle = RequestBundle::with_client(
+            &omdb,
+            "",
+            "up",
+            &unfiltered,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        assert!(bundle.suggestions().is_empty());
+    }
+
+    #[test]
+    fn too_many_results_suggestion() {
+        let too_many = r#"{"Response":"False","Error":"Too many results."}"#;
+        let client = |_| Ok(too_many.to_owned());
+        let filters = Filters::default();
+        let bundle = RequestBundle::with_client(
+            &client,
+            "",
+            "a",
+            &filters,
+            DEFAULT_MAX_REQUESTS_PER_SEARCH,
+        );
+        match bundle.get_results(10, false).unwrap_err() {
+            RequestError::Omdb(err) => {
+                assert_eq!(err, OmdbError::TooManyResults);
+                assert!(!err.is_fatal());
+                assert_eq!(
+                    err.suggestion(),
+                    Some("Try adding -y or -t to narrow your search")
+                );
+            },
+            other => panic!("got {other:?}"),
+        }
+        // Nothing else has a suggestion
+        assert_eq!(OmdbError::NotFound.suggestion(), None);
+        assert_eq!(OmdbError::InvalidApiKey.suggestion(), None);
+    }
+
+    #[test]
+    fn fake_api_key_check() {
+        let request = |api_key| {
+            get("https://www.omdbapi.com/").with_param("apikey", api_key)
+        };
+        let omdb = FakeOmdb(vec![
+            (request("abc123"), INVALID_KEY.to_owned()),
+            (
+                request("123abc"),
+                r#"{"Response":"False","Error":"Incorrect IMDb ID."}"#
+                    .to_owned(),
+            ),
+        ]);
+        assert!(matches!(
+            test_api_key_with(&omdb, "abc123"),
+            Err(ApiKeyError::Unauthorised)
+        ));
+        assert!(test_api_key_with(&omdb, "123abc").is_ok());
+        // Checked before any request is made
+        assert!(matches!(
+            test_api_key_with(&omdb, "not hex"),
+            Err(ApiKeyError::InvalidFormat)
+        ));
+
+        let unavailable = |_| Err(RequestError::Status(503));
+        let err = test_api_key_with(&unavailable, "123abc").unwrap_err();
+        assert!(matches!(err, ApiKeyError::UnexpectedStatus(503)));
+        assert!(err.is_fatal());
+    }
+
+    #[test]
+    fn merge_with_piped() {
+        let piped = DESERIALISED[..2]
+            .iter()
+            .cloned()
+            .map(SearchResult::from)
+            .collect::<Vec<_>>();
+        // Overlaps with the piped results on "1917"
+        let mut searched = DESERIALISED[1..]
+            .iter()
+            .cloned()
+            .map(SearchResult::from)
+            .collect::<Vec<_>>();
+        // The same media as a piped result, but under a different ID
+        let mut duplicate = piped[0].clone();
+        duplicate.imdb_id = "tt0000001".into();
+        searched.push(duplicate);
+        let merged = merge_results(piped, searched)
+            .into_iter()
+            .map(|sr| sr.imdb_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            merged,
+            ["tt1049413", "tt8579674", "tt2802144", "tt1590961"]
+        );
+    }
+
+    #[test]
+    fn refine_year() {
+        let breakout_kings = &DESERIALISED[3];
+        let mut sr = SearchResult {
+            title: "Breakout Kings".into(),
+            imdb_id: "tt1590961".into(),
+            media_type: MediaType::SERIES,
+            year: "2011-".parse().unwrap(),
+            poster: None,
+        };
+        assert!(sr.year.is_open_ended());
+        sr.refine_year(breakout_kings);
+        assert_eq!(sr.year, Year(2011..=2012));
+        assert!(!sr.year.is_open_ended());
+
+        // Entries for other IDs are ignored
+        let mut other = SearchResult {
+            imdb_id: "tt2085059".into(),
+            ..sr.clone()
+        };
+        other.year = "2011-".parse().unwrap();
+        other.refine_year(breakout_kings);
+        assert!(other.year.is_open_ended());
+    }
+
+    #[test]
+    fn representations() {
+        let sr = SearchResult {
+            title: "Breakout Kings".into(),
+            imdb_id: "tt1590961".into(),
+            media_type: MediaType::SERIES,
+            year: Year(2011..=2012),
+            poster: None,
+        };
+        assert_eq!(sr.to_string(), "Breakout Kings (series, 2011-2012)");
+        assert_eq!(sr.list_label(), "Breakout Kings (series, 2011-2012)");
+    }
+
+    #[test]
+    fn media_type_iter() {
+        let all = MediaType::ALL.iter().collect::<Vec<_>>();
+        assert_eq!(all, [MediaType::MOVIE, MediaType::SERIES, MediaType::GAME]);
+        let all = MediaType::ALL.str_iter().collect::<Vec<_>>();
+        assert_eq!(all, ["movie", "series", "game"]);
+        let some = MediaType::MOVIE | MediaType::GAME;
+        assert_eq!(some.iter().count(), 2);
+        assert!(some.is_movie() && !some.is_series() && some.is_game());
+        assert!(MediaType::ALL.is_series());
+    }
+
+    #[test]
+    fn media_type_serde() {
+        let round_trip = |media_type: MediaType, json: &str| {
+            assert_eq!(serde_json::to_string(&media_type).unwrap(), json);
+            let parsed = serde_json::from_str::<MediaType>(json).unwrap();
+            assert_eq!(parsed, media_type);
+        };
+        round_trip(MediaType::MOVIE, r#""movie""#);
+        round_trip(MediaType::GAME, r#""game""#);
+        round_trip(
+            MediaType::MOVIE | MediaType::SERIES,
+            r#"["movie","series"]"#,
+        );
+        round_trip(MediaType::ALL, r#"["movie","series","game"]"#);
+        // How multiple types used to be serialized
+        let legacy = serde_json::from_str::<MediaType>(r#""movie/series""#);
+        assert_eq!(legacy.unwrap(), MediaType::MOVIE | MediaType::SERIES);
+        // A single type in an array is fine too, but not no types or bad ones
+        let one = serde_json::from_str::<MediaType>(r#"["series"]"#);
+        assert_eq!(one.unwrap(), MediaType::SERIES);
+        assert!(serde_json::from_str::<MediaType>("[]").is_err());
+        assert!(serde_json::from_str::<MediaType>(r#""movie/film""#).is_err());
+        assert!(serde_json::from_str::<MediaType>(r#"["episode"]"#).is_err());
+    }
+
+    fn sortable() -> Vec<SearchResult> {
+        [
+            ("Up", "tt1049413", 2009..=2009),
+            ("bolt", "tt0397892", 2008..=2008),
+            ("Coco", "tt2380307", 2017..=2017),
+            ("Atlantis", "tt0230011", 2001..=2001),
+            ("Wall-E", "tt0910970", 2008..=2008),
+        ]
+        .into_iter()
+        .map(|(title, imdb_id, years)| SearchResult {
+            title: title.into(),
+            imdb_id: imdb_id.into(),
+            media_type: MediaType::MOVIE,
+            year: Year(years),
+            poster: None,
+        })
+        .collect()
+    }
+
+    fn sorted_titles(key: SortKey) -> Vec<String> {
+        let mut search_results = sortable();
+        sort_results(&mut search_results, key);
+        search_results.into_iter().map(|sr| sr.title).collect()
+    }
+
+    #[test]
+    fn sort_keys() {
+        assert_eq!("relevance".parse(), Ok(SortKey::Relevance));
+        assert_eq!("Year".parse(), Ok(SortKey::Year));
+        assert_eq!("year-desc".parse(), Ok(SortKey::YearDesc));
+        assert_eq!("title".parse(), Ok(SortKey::Title));
+        assert_eq!(
+            "rating".parse::<SortKey>(),
+            Err(SortKeyParseError("rating".into()))
+        );
+    }
+
+    #[test]
+    fn sort_relevance() {
+        assert_eq!(
+            sorted_titles(SortKey::Relevance),
+            ["Up", "bolt", "Coco", "Atlantis", "Wall-E"]
+        );
+    }
+
+    #[test]
+    fn sort_year() {
+        // Bolt and Wall-E tie, so stay in relevance order
+        assert_eq!(
+            sorted_titles(SortKey::Year),
+            ["Atlantis", "bolt", "Wall-E", "Up", "Coco"]
+        );
+        assert_eq!(
+            sorted_titles(SortKey::YearDesc),
+            ["Coco", "Up", "bolt", "Wall-E", "Atlantis"]
+        );
+    }
+
+    #[test]
+    fn sort_title() {
+        assert_eq!(
+            sorted_titles(SortKey::Title),
+            ["Atlantis", "bolt", "Coco", "Up", "Wall-E"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_ranking() {
+        let mut search_results = sortable();
+        for (sr, title) in search_results.iter_mut().zip([
+            "The Thing from Another World",
+            "The Things We Do",
+            "Thing",
+            "the thing ",
+            "The Thin Man",
+        ]) {
+            sr.title = title.into();
+        }
+        rank_by_similarity(&mut search_results, "The Thing");
+        let titles = search_results
+            .into_iter()
+            .map(|sr| sr.title)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            [
+                "the thing ",
+                "The Thin Man",
+                "The Things We Do",
+                "Thing",
+                "The Thing from Another World",
+            ]
+        );
+
+        // Both are exact matches, so they stay in relevance order
+        let mut search_results = sortable();
+        search_results[4].title = "Up".into();
+        rank_by_similarity(&mut search_results, "UP");
+        let ids = search_results
+            .iter()
+            .map(|sr| sr.imdb_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids[..2], ["tt1049413", "tt0910970"]);
+    }
+
+    #[test]
+    fn exact_titles() {
+        let kingsman = || {
+            let mut search_results = DESERIALISED
+                .iter()
+                .cloned()
+                .map(SearchResult::from)
+                .collect::<Vec<_>>();
+            let mut sequel = search_results[2].clone();
+            sequel.title = "Kingsman: The Golden Circle".into();
+            sequel.imdb_id = "tt4649466".into();
+            search_results.push(sequel);
+            search_results
+        };
+        let ids = |search_results: Vec<SearchResult>| {
+            search_results
+                .into_iter()
+                .map(|sr| sr.imdb_id)
+                .collect::<Vec<_>>()
+        };
+
+        let exact =
+            keep_exact_titles(kingsman(), " kingsman: the SECRET service");
+        assert_eq!(ids(exact.unwrap()), ["tt2802144"]);
+        let exact =
+            keep_exact_titles(kingsman(), "Kingsman: The Golden Circle");
+        assert_eq!(ids(exact.unwrap()), ["tt4649466"]);
+
+        let err = keep_exact_titles(kingsman(), "Kingsman").unwrap_err();
+        assert!(
+            matches!(&err, RequestError::Omdb(OmdbError::NotFound)),
+            "got {err:?}"
+        );
+    }
+
     static DESERIALISED: Lazy<Vec<Entry>> = Lazy::new(|| {
         INPUTS
             .iter()
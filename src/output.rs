@@ -0,0 +1,907 @@
+use crate::omdb::BatchResult;
+use crate::omdb::SearchResult;
+use crate::ClipboardError;
+use crate::OutputFormat;
+use crate::WEB_URL;
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use std::{env, fmt, iter};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const DEFAULT_PAGER: &str = "less";
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Pager {
+    // Only page if the output is longer than the terminal is tall
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+// Where the final output goes: stdout, unless --output was given
+// Output that might be shown through the pager is buffered so that its length
+// can be checked first
+pub enum Output {
+    Stdout(io::Stdout),
+    File(File),
+    Buffered(Vec<u8>, Pager),
+}
+
+impl Output {
+    // Appending leaves any existing contents of the file alone, so that
+    // multiple runs can build up one file
+    pub fn open(
+        path: Option<&Path>,
+        append: bool,
+        pager: Pager,
+    ) -> io::Result<Self> {
+        match path {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .append(append)
+                    .truncate(!append)
+                    .open(path)?;
+                Ok(Output::File(file))
+            },
+            None if pager != Pager::Never => {
+                Ok(Output::Buffered(Vec::new(), pager))
+            },
+            None => Ok(Output::Stdout(io::stdout())),
+        }
+    }
+
+    // How many columns wide the terminal being written to is, if it is one
+    pub fn width(&self) -> Option<usize> {
+        use std::io::IsTerminal;
+        match self {
+            Output::File(_) => None,
+            _ if !io::stdout().is_terminal() => None,
+            _ => crossterm::terminal::size()
+                .ok()
+                .map(|(columns, _)| usize::from(columns)),
+        }
+    }
+
+    // Must be called once all output is written, so buffered output is shown
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Output::Buffered(buf, pager) => {
+                let height = crossterm::terminal::size()
+                    .map(|(_, rows)| rows)
+                    .unwrap_or(u16::MAX);
+                if should_page(&buf, height, pager) {
+                    match page(&buf) {
+                        Ok(()) => return Ok(()),
+                        Err(why) => log::warn!(
+                            "couldn't open pager, printing instead: {why}"
+                        ),
+                    }
+                }
+                io::stdout().write_all(&buf)
+            },
+            mut other => other.flush(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(stdout) => stdout.write(buf),
+            Output::File(file) => file.write(buf),
+            Output::Buffered(vec, _) => vec.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File(file) => file.flush(),
+            Output::Buffered(vec, _) => vec.flush(),
+        }
+    }
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Output::Stdout(_) => write!(f, "Output::Stdout"),
+            Output::File(file) => write!(f, "Output::File({file:?})"),
+            Output::Buffered(vec, pager) => {
+                write!(f, "Output::Buffered({} bytes, {pager:?})", vec.len())
+            },
+        }
+    }
+}
+
+fn should_page(buf: &[u8], terminal_height: u16, pager: Pager) -> bool {
+    match pager {
+        Pager::Always => true,
+        Pager::Never => false,
+        Pager::Auto => {
+            let lines = buf.iter().filter(|&&b| b == b'\n').count();
+            lines >= terminal_height as usize
+        },
+    }
+}
+
+// Use $PAGER like git does, which may include arguments
+fn page(buf: &[u8]) -> io::Result<()> {
+    let pager = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.into());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or(DEFAULT_PAGER);
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    match stdin.write_all(buf) {
+        // User quit the pager before reading everything
+        Err(why) if why.kind() == io::ErrorKind::BrokenPipe => {},
+        other => other?,
+    }
+    drop(stdin);
+    child.wait()?;
+    Ok(())
+}
+
+// Fails on headless systems, where there's no clipboard to copy to
+pub fn copy_to_clipboard(text: &str) -> Result<(), ClipboardError> {
+    arboard::Clipboard::new()?.set_text(text)?;
+    Ok(())
+}
+
+// Shown on stderr while searching, so slow connections don't look like
+// they've hung. It's cleared when it's dropped too, so errors never leave it
+// behind
+pub struct Spinner(ProgressBar);
+
+impl Spinner {
+    // Only for human output in interactive mode, which already needs stdin
+    // and stdout to be a terminal
+    pub fn new(interactive: bool, format: OutputFormat) -> Option<Self> {
+        if !interactive || !matches!(format, OutputFormat::Human) {
+            return None;
+        }
+        let bar = ProgressBar::new_spinner().with_message("Searching…");
+        bar.enable_steady_tick(Duration::from_millis(100));
+        Some(Spinner(bar))
+    }
+
+    // Requests made after it's been cleared (like loading more results in
+    // the interactive list) are ignored
+    pub fn requests_made(&self, made: usize, most: usize) {
+        if !self.0.is_finished() {
+            self.0
+                .set_message(format!("Searching… ({made}/{most} requests)"));
+        }
+    }
+
+    pub fn clear(&self) {
+        self.0.finish_and_clear();
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+pub const DEFAULT_DELIMITER: char = '\t';
+
+// Shown in place of an ID for --batch titles without any results
+pub const BATCH_NOT_FOUND: &str = "NOT_FOUND";
+
+// "<title>\t<imdb_id>" for each --batch title, in the order they were given
+pub fn batch(batch_results: &[BatchResult], print_url: bool) -> String {
+    let mut buf = String::new();
+    for br in batch_results {
+        buf.push_str(&br.search_term);
+        buf.push('\t');
+        match &br.result {
+            Some(sr) => {
+                if print_url {
+                    buf.push_str(WEB_URL);
+                }
+                buf.push_str(&sr.imdb_id);
+            },
+            None => buf.push_str(BATCH_NOT_FOUND),
+        }
+        buf.push('\n');
+    }
+    buf
+}
+
+// "<imdb_id>\t<title> (<type>, <year>)" for each favourite, for
+// --list-favourites without a --format
+pub fn favourites(search_results: &[SearchResult], print_url: bool) -> String {
+    let mut buf = String::new();
+    for sr in search_results {
+        if print_url {
+            buf.push_str(WEB_URL);
+        }
+        buf.push_str(&sr.imdb_id);
+        buf.push('\t');
+        buf.push_str(&sr.to_string());
+        buf.push('\n');
+    }
+    buf
+}
+
+// The fields of a search result that can be picked with --fields
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OutputField {
+    Title,
+    Year,
+    ImdbId,
+    MediaType,
+    Poster,
+}
+
+// What tsv, csv, table, and markdown show without --fields
+pub const DEFAULT_FIELDS: [OutputField; 4] = [
+    OutputField::Title,
+    OutputField::Year,
+    OutputField::ImdbId,
+    OutputField::MediaType,
+];
+
+impl OutputField {
+    pub fn from_name(name: &str) -> Option<Self> {
+        use OutputField::*;
+        match name.trim().to_ascii_lowercase().as_str() {
+            "title" => Some(Title),
+            "year" => Some(Year),
+            "imdb_id" | "id" => Some(ImdbId),
+            "media_type" | "type" => Some(MediaType),
+            "poster" => Some(Poster),
+            _ => None,
+        }
+    }
+
+    // Matches the names used when serialising a SearchResult
+    pub fn name(&self) -> &'static str {
+        use OutputField::*;
+        match self {
+            Title => "title",
+            Year => "year",
+            ImdbId => "imdb_id",
+            MediaType => "media_type",
+            Poster => "poster",
+        }
+    }
+
+    // For text formats, where a missing poster is left empty
+    fn text(&self, sr: &SearchResult) -> String {
+        use OutputField::*;
+        match self {
+            Title => sr.title.clone(),
+            Year => sr.year.to_string(),
+            ImdbId => sr.imdb_id.clone(),
+            MediaType => sr.media_type.to_string(),
+            Poster => sr.poster.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// A search result serialised with only some of its fields, in the order given
+pub struct Selected<'a> {
+    search_result: &'a SearchResult,
+    fields: &'a [OutputField],
+}
+
+impl Serialize for Selected<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use OutputField::*;
+        let sr = self.search_result;
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for field in self.fields {
+            match field {
+                Title => map.serialize_entry(field.name(), &sr.title)?,
+                Year => map.serialize_entry(field.name(), &sr.year)?,
+                ImdbId => map.serialize_entry(field.name(), &sr.imdb_id)?,
+                MediaType => {
+                    map.serialize_entry(field.name(), &sr.media_type)?
+                },
+                Poster => map.serialize_entry(field.name(), &sr.poster)?,
+            }
+        }
+        map.end()
+    }
+}
+
+pub fn select<'a>(
+    search_results: &'a [SearchResult],
+    fields: &'a [OutputField],
+) -> Vec<Selected<'a>> {
+    search_results
+        .iter()
+        .map(|search_result| Selected {
+            search_result,
+            fields,
+        })
+        .collect()
+}
+
+// One result per line, with fields separated by the delimiter
+// Any occurrences of the delimiter (or newlines) within a field are replaced
+// with spaces so that the columns always line up
+pub fn delimited(
+    search_results: &[SearchResult],
+    delimiter: char,
+    fields: &[OutputField],
+) -> String {
+    let sanitise = |field: String| field.replace([delimiter, '\n', '\r'], " ");
+    let mut buf = String::new();
+    for sr in search_results {
+        for (index, field) in fields.iter().enumerate() {
+            if index != 0 {
+                buf.push(delimiter);
+            }
+            buf.push_str(&sanitise(field.text(sr)));
+        }
+        buf.push('\n');
+    }
+    buf
+}
+
+// As described by RFC 4180: a header row, CRLF line endings, and fields are
+// quoted if they contain commas, quotes, or line breaks
+pub fn csv<W: Write>(
+    search_results: &[SearchResult],
+    fields: &[OutputField],
+    writer: W,
+) -> csv::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(writer);
+    writer.write_record(fields.iter().map(OutputField::name))?;
+    for sr in search_results {
+        writer.write_record(fields.iter().map(|field| field.text(sr)))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// A header row, then one result per line with each field padded to line up
+// with the ones above. When a width is given, the widest column is cut short
+// so the lines fit in it
+pub fn table(
+    search_results: &[SearchResult],
+    fields: &[OutputField],
+    width: Option<usize>,
+) -> String {
+    const GAP: &str = "  ";
+    let header = fields.iter().map(|field| field.name().to_owned()).collect();
+    let rows = search_results.iter().map(|sr| {
+        fields
+            .iter()
+            .map(|field| field.text(sr).replace(['\t', '\n', '\r'], " "))
+            .collect::<Vec<_>>()
+    });
+    let rows = iter::once(header).chain(rows).collect::<Vec<Vec<_>>>();
+    let mut widths = (0..fields.len())
+        .map(|column| rows.iter().map(|row| row[column].width()).max())
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+
+    let total = widths.iter().sum::<usize>()
+        + GAP.len() * fields.len().saturating_sub(1);
+    if let Some(overflow) = width.and_then(|width| total.checked_sub(width)) {
+        if let Some((column, widest)) = widths
+            .iter_mut()
+            .enumerate()
+            .max_by_key(|(_, width)| **width)
+        {
+            // The header is always shown in full
+            let header = fields[column].name().len();
+            *widest = widest.saturating_sub(overflow).max(header);
+        }
+    }
+
+    let mut buf = String::new();
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| {
+                let cell = truncate(cell, width);
+                let padding = width.saturating_sub(cell.width());
+                format!("{cell}{}", " ".repeat(padding))
+            })
+            .join(GAP);
+        buf.push_str(line.trim_end());
+        buf.push('\n');
+    }
+    buf
+}
+
+// A GitHub-flavoured Markdown table, with each IMDb ID linking to its page.
+// Pipes in fields are escaped so they don't start a new column
+pub fn markdown(
+    search_results: &[SearchResult],
+    fields: &[OutputField],
+) -> String {
+    let escape =
+        |field: String| field.replace('|', "\\|").replace(['\n', '\r'], " ");
+    let mut buf = String::new();
+    let header = fields.iter().map(OutputField::name).join(" | ");
+    buf.push_str(&format!("| {header} |\n"));
+    let separator = fields.iter().map(|_| "---").join(" | ");
+    buf.push_str(&format!("| {separator} |\n"));
+    for sr in search_results {
+        let row = fields
+            .iter()
+            .map(|field| match field {
+                OutputField::ImdbId => {
+                    let imdb_id = escape(field.text(sr));
+                    format!("[{imdb_id}]({WEB_URL}{imdb_id}/)")
+                },
+                _ => escape(field.text(sr)),
+            })
+            .join(" | ");
+        buf.push_str(&format!("| {row} |\n"));
+    }
+    buf
+}
+
+// Cuts s down to at most width columns, ending with … if anything was cut
+fn truncate(s: &str, width: usize) -> Cow<'_, str> {
+    if s.width() <= width {
+        return Cow::Borrowed(s);
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let c_width = c.width().unwrap_or(0);
+        // Leave room for the …
+        if used + c_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        used += c_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    Cow::Owned(truncated)
+}
+
+// Pretty-printed, unless --compact was given
+pub fn json<T: Serialize + ?Sized>(
+    value: &T,
+    compact: bool,
+) -> serde_json::Result<String> {
+    match compact {
+        true => serde_json::to_string(value),
+        false => serde_json::to_string_pretty(value),
+    }
+}
+
+// The JSON output of a search, so scripts can tell when OMDb has more results
+// than were shown
+#[derive(Serialize)]
+pub struct WithTotal<'a, T: ?Sized> {
+    pub total_results: u32,
+    pub results: &'a T,
+}
+
+// Newline delimited JSON, one result per line. Each line is flushed as soon as
+// it's written, so tools reading from a pipe can start straight away
+pub fn ndjson<T: Serialize, W: Write>(
+    search_results: &[T],
+    mut writer: W,
+) -> io::Result<()> {
+    for sr in search_results {
+        serde_json::to_writer(&mut writer, sr)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// A list of links to each result's IMDb page, ready to paste into a web page
+#[cfg(feature = "html")]
+pub fn html(search_results: &[SearchResult]) -> String {
+    let mut buf = String::from("<ul>\n");
+    for sr in search_results {
+        buf.push_str(&format!(
+            "  <li><a href=\"{}{}\">{}</a> ({}, {})</li>\n",
+            WEB_URL,
+            escape_html(&sr.imdb_id),
+            escape_html(&sr.title),
+            sr.media_type,
+            sr.year,
+        ));
+    }
+    buf.push_str("</ul>\n");
+    buf
+}
+
+// TOML can't have an array at the top level, so each result is a table in a
+// "results" array
+#[cfg(feature = "toml")]
+#[derive(Serialize)]
+pub struct TomlOutput<'a, T> {
+    pub results: &'a [T],
+}
+
+// TOML has no null, so a missing poster is left out
+#[cfg(feature = "toml")]
+pub fn toml<T: Serialize>(results: &[T]) -> Result<String, toml::ser::Error> {
+    toml::to_string(&TomlOutput { results })
+}
+
+#[cfg(feature = "html")]
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::omdb::MediaType;
+    use crate::Year;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("imdb-id-{}-{name}", std::process::id()));
+        path
+    }
+
+    #[test]
+    fn append_two_runs() {
+        let path = temp_path("append");
+        let _ = fs::remove_file(&path);
+        for id in ["tt1049413", "tt8579674"] {
+            let mut output =
+                Output::open(Some(&path), true, Pager::Auto).unwrap();
+            writeln!(output, "{id}").unwrap();
+            output.finish().unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "tt1049413\ntt8579674\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn overwrite_without_append() {
+        let path = temp_path("overwrite");
+        for id in ["tt1049413", "tt8579674"] {
+            let mut output =
+                Output::open(Some(&path), false, Pager::Auto).unwrap();
+            writeln!(output, "{id}").unwrap();
+            output.finish().unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "tt8579674\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn json_to_file() {
+        let path = temp_path("json");
+        let mut output = Output::open(Some(&path), false, Pager::Auto).unwrap();
+        writeln!(output, "{}", json(&kingsman(), false).unwrap()).unwrap();
+        output.finish().unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written[0]["imdb_id"], "tt2802144");
+        assert_eq!(written.as_array().unwrap().len(), kingsman().len());
+        fs::remove_file(&path).unwrap();
+
+        // Somewhere that can't be written to
+        let path = temp_path("missing").join("ids.json");
+        let err = Output::open(Some(&path), false, Pager::Auto).unwrap_err();
+        assert!(matches!(
+            crate::FinalError::from(err),
+            crate::FinalError::FormatOutput(_)
+        ));
+    }
+
+    fn kingsman() -> Vec<SearchResult> {
+        vec![
+            SearchResult {
+                title: "Kingsman: The Secret Service".into(),
+                imdb_id: "tt2802144".into(),
+                media_type: MediaType::MOVIE,
+                year: Year(2014..=2014),
+                poster: None,
+            },
+            SearchResult {
+                title: "Kingsman | The Golden Circle".into(),
+                imdb_id: "tt4649466".into(),
+                media_type: MediaType::MOVIE,
+                year: Year(2017..=2017),
+                poster: None,
+            },
+        ]
+    }
+
+    // Needs a clipboard, and replaces what's on it, so only runs when asked
+    // to with cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn clipboard() {
+        // Kept open so that what's copied is still there to be read back
+        let mut clipboard = arboard::Clipboard::new().unwrap();
+        copy_to_clipboard("tt2802144").unwrap();
+        assert_eq!(clipboard.get_text().unwrap(), "tt2802144");
+    }
+
+    #[test]
+    fn paging() {
+        let three_lines = b"one\ntwo\nthree\n";
+        assert!(!should_page(three_lines, 24, Pager::Auto));
+        assert!(should_page(three_lines, 3, Pager::Auto));
+        assert!(should_page(three_lines, 2, Pager::Auto));
+        assert!(should_page(three_lines, 24, Pager::Always));
+        assert!(!should_page(three_lines, 2, Pager::Never));
+    }
+
+    #[test]
+    fn spinner_only_when_interactive() {
+        assert!(Spinner::new(false, OutputFormat::Human).is_none());
+        assert!(Spinner::new(true, OutputFormat::Json).is_none());
+        assert!(Spinner::new(false, OutputFormat::Json).is_none());
+        let spinner = Spinner::new(true, OutputFormat::Human).unwrap();
+        spinner.clear();
+        // Ignored once cleared
+        spinner.requests_made(1, 10);
+        assert!(spinner.0.is_finished());
+    }
+
+    #[test]
+    fn tab_delimited() {
+        assert_eq!(
+            delimited(&kingsman(), DEFAULT_DELIMITER, &DEFAULT_FIELDS),
+            "Kingsman: The Secret Service\t2014\ttt2802144\tmovie\n\
+            Kingsman | The Golden Circle\t2017\ttt4649466\tmovie\n",
+        );
+    }
+
+    #[test]
+    fn aligned_table() {
+        let full = table(&kingsman(), &DEFAULT_FIELDS, None);
+        let lines = full.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "title                         year  imdb_id    media_type",
+        );
+        assert_eq!(
+            lines[1],
+            "Kingsman: The Secret Service  2014  tt2802144  movie",
+        );
+        // Every row has a field under each heading
+        for line in &lines {
+            assert_eq!(line.split("  ").filter(|s| !s.is_empty()).count(), 4);
+        }
+
+        // Only the widest column (the title) is cut short to fit
+        let narrow = table(&kingsman(), &DEFAULT_FIELDS, Some(40));
+        assert!(narrow.lines().all(|line| line.width() <= 40));
+        assert!(narrow.contains("Kingsman: …  2014  tt2802144  movie\n"));
+
+        // Wide characters take up two columns each
+        let wide = vec![SearchResult {
+            title: "千と千尋の神隠し".into(),
+            ..kingsman().remove(0)
+        }];
+        let fields = [OutputField::Title, OutputField::Year];
+        let narrow = table(&wide, &fields, Some(13));
+        assert_eq!(narrow, "title    year\n千と千…  2014\n");
+    }
+
+    #[test]
+    fn markdown_table() {
+        assert_eq!(
+            markdown(&kingsman(), &DEFAULT_FIELDS),
+            "| title | year | imdb_id | media_type |\n\
+            | --- | --- | --- | --- |\n\
+            | Kingsman: The Secret Service | 2014 | [tt2802144](https://www.imdb.com/title/tt2802144/) | movie |\n\
+            | Kingsman \\| The Golden Circle | 2017 | [tt4649466](https://www.imdb.com/title/tt4649466/) | movie |\n",
+        );
+    }
+
+    #[test]
+    fn delimiter_in_title() {
+        assert_eq!(
+            delimited(&kingsman(), '|', &DEFAULT_FIELDS),
+            "Kingsman: The Secret Service|2014|tt2802144|movie\n\
+            Kingsman   The Golden Circle|2017|tt4649466|movie\n",
+        );
+        assert_eq!(
+            delimited(&kingsman()[..1], ':', &DEFAULT_FIELDS),
+            "Kingsman  The Secret Service:2014:tt2802144:movie\n",
+        );
+    }
+
+    #[test]
+    fn batch_lines() {
+        let batch_results = vec![
+            BatchResult {
+                search_term: "kingsman".into(),
+                result: kingsman().into_iter().next(),
+            },
+            BatchResult {
+                search_term: "nothing at all".into(),
+                result: None,
+            },
+        ];
+        assert_eq!(
+            batch(&batch_results, false),
+            "kingsman\ttt2802144\nnothing at all\tNOT_FOUND\n",
+        );
+        assert_eq!(
+            batch(&batch_results[..1], true),
+            "kingsman\thttps://www.imdb.com/title/tt2802144\n",
+        );
+    }
+
+    #[test]
+    fn favourites_lines() {
+        assert_eq!(
+            favourites(&kingsman(), false),
+            "tt2802144\tKingsman: The Secret Service (movie, 2014)\n\
+            tt4649466\tKingsman | The Golden Circle (movie, 2017)\n",
+        );
+        assert!(favourites(&kingsman(), true)
+            .starts_with("https://www.imdb.com/title/tt2802144\t"));
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let mut results = kingsman();
+        results[0].title = "Kingsman, The Secret Service".into();
+        results[1].title = "Kingsman: \"The Golden Circle\"".into();
+        let mut buf = Vec::new();
+        csv(&results, &DEFAULT_FIELDS, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "title,year,imdb_id,media_type\r\n\
+            \"Kingsman, The Secret Service\",2014,tt2802144,movie\r\n\
+            \"Kingsman: \"\"The Golden Circle\"\"\",2017,tt4649466,movie\r\n",
+        );
+    }
+
+    #[test]
+    fn only_selected_fields() {
+        use OutputField::*;
+        let mut results = kingsman();
+        results[0].poster = Some("https://example.com/poster.jpg".into());
+        let fields = [ImdbId, Poster, Title];
+
+        let json = serde_json::to_value(select(&results, &fields)).unwrap();
+        let keys = json[0].as_object().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(keys.len(), 3);
+        for key in ["imdb_id", "poster", "title"] {
+            assert!(keys.contains(&&key.to_owned()), "missing {key}");
+        }
+        assert_eq!(json[0]["poster"], "https://example.com/poster.jpg");
+        assert_eq!(json[1]["poster"], serde_json::Value::Null);
+        // The order given is kept
+        let json = serde_json::to_string(&select(&results[..1], &fields));
+        assert!(json
+            .unwrap()
+            .starts_with(r#"[{"imdb_id":"tt2802144","poster""#));
+
+        let mut buf = Vec::new();
+        csv(&results, &fields, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "imdb_id,poster,title\r\n\
+            tt2802144,https://example.com/poster.jpg,Kingsman: The Secret Service\r\n\
+            tt4649466,,Kingsman | The Golden Circle\r\n",
+        );
+        assert_eq!(
+            delimited(&results, '\t', &[Year, ImdbId]),
+            "2014\ttt2802144\n2017\ttt4649466\n",
+        );
+    }
+
+    #[test]
+    fn compact_json() {
+        let results = kingsman();
+        let compact = json(&results, true).unwrap();
+        assert!(!compact.contains('\n'), "compact json has newlines");
+        let pretty = json(&results, false).unwrap();
+        assert!(pretty.contains('\n'));
+        // Only the whitespace differs
+        let parse = |s: &str| serde_json::from_str::<serde_json::Value>(s);
+        assert_eq!(parse(&compact).unwrap(), parse(&pretty).unwrap());
+    }
+
+    #[test]
+    fn ndjson_lines() {
+        let results = kingsman();
+        let mut buf = Vec::new();
+        ndjson(&results, &mut buf).unwrap();
+        let lines = String::from_utf8(buf).unwrap();
+        // SearchResult only deserialises from OMDb's field names, so compare
+        // as plain JSON
+        let parsed = lines
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let expected = results
+            .iter()
+            .map(|sr| serde_json::to_value(sr).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parsed, expected);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_tables() {
+        let mut results = kingsman();
+        results[1].title = "Kingsman: \"The Golden Circle\"".into();
+        let parsed =
+            toml::from_str::<toml::Value>(&toml(&results).unwrap()).unwrap();
+        let tables = parsed["results"].as_array().unwrap();
+        assert_eq!(tables.len(), 2);
+        assert_eq!(
+            tables[1]["title"].as_str(),
+            Some("Kingsman: \"The Golden Circle\""),
+        );
+        assert_eq!(tables[0]["year"].as_str(), Some("2014"));
+        assert_eq!(tables[0]["media_type"].as_str(), Some("movie"));
+        // Neither has a poster
+        assert!(tables.iter().all(|table| table.get("poster").is_none()));
+
+        let fields = [OutputField::ImdbId, OutputField::Poster];
+        assert_eq!(
+            toml(&select(&results, &fields)).unwrap(),
+            "[[results]]\n\
+            imdb_id = \"tt2802144\"\n\
+            \n\
+            [[results]]\n\
+            imdb_id = \"tt4649466\"\n",
+        );
+    }
+
+    #[cfg(feature = "html")]
+    #[test]
+    fn html_escaped() {
+        let mut results = kingsman();
+        results[1].title = "Kingsman & <The Golden Circle>".into();
+        assert_eq!(
+            html(&results),
+            "<ul>\n  \
+            <li><a href=\"https://www.imdb.com/title/tt2802144\">Kingsman: The Secret Service</a> (movie, 2014)</li>\n  \
+            <li><a href=\"https://www.imdb.com/title/tt4649466\">Kingsman &amp; &lt;The Golden Circle&gt;</a> (movie, 2017)</li>\n\
+            </ul>\n",
+        );
+    }
+}
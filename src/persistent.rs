@@ -1,54 +1,1126 @@
-use crate::DiskError;
-use once_cell::sync::Lazy;
+use crate::omdb::{MediaType, SearchResult};
+use crate::{DiskError, OutputFormat, Year};
+use fs2::FileExt;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::fs::{File, OpenOptions};
-use std::io;
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::fs::{self, File};
 use std::io::{BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{env, io};
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::OffsetDateTime;
 
-static CONFIG_PATH: Lazy<PathBuf> = Lazy::new(|| {
+static CONFIG_PATH: OnceCell<PathBuf> = OnceCell::new();
+static PROFILE: OnceCell<String> = OnceCell::new();
+
+// Should be called before the config is loaded or saved, as the first path
+// set (or resolved from the environment) is used from then on
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+// Like set_config_path, this must be called before the config is used. Has
+// no effect if the config path is set
+pub fn set_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+fn config_path() -> &'static Path {
+    CONFIG_PATH.get_or_init(|| {
+        let path = resolve_config_path(env::var_os("IMDB_ID_CONFIG"));
+        match PROFILE.get() {
+            Some(profile) => profile_path(&path, profile),
+            None => path,
+        }
+    })
+}
+
+// Each profile is its own config file next to the default one, e.g.
+// imdb-id.json becomes imdb-id.work.json
+fn profile_path(path: &Path, profile: &str) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_owned();
+    name.push(".");
+    name.push(profile);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+// IMDB_ID_CONFIG if it's usable, otherwise the platform's config directory
+fn resolve_config_path(env: Option<OsString>) -> PathBuf {
+    match env.map(PathBuf::from) {
+        Some(path) if path.is_absolute() => return path,
+        Some(path) => log::warn!(
+            "IMDB_ID_CONFIG must be an absolute path, ignoring {}",
+            path.display(),
+        ),
+        None => {},
+    }
     let mut config_path =
         dirs::config_dir().expect("Platform unsupported by dirs");
     config_path.push("imdb-id.json");
     config_path
-});
+}
+
+// Next to the config file, wherever that is
+fn favourites_path() -> PathBuf {
+    config_path().with_file_name("imdb-id-favourites.json")
+}
+
+// Also next to the config file
+fn request_count_path() -> PathBuf {
+    config_path().with_file_name("imdb-id-requests.json")
+}
+
+// Also next to the config file, so it moves with --config and IMDB_ID_CONFIG
+fn cache_dir() -> PathBuf {
+    config_path().with_file_name("imdb-id-cache")
+}
+
+// On a fresh machine, the directory the config goes in might not exist yet
+fn create_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    }
+}
+
+// e.g. imdb-id.json.tmp, next to imdb-id.json
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+// Where a file is written before being moved over the real one
+fn temp_sibling(path: &Path) -> PathBuf {
+    sibling(path, ".tmp")
+}
+
+// Moving the file into place is atomic, so if imdb-id stops part way through
+// writing, the old file is left as it was. Two runs saving at once take it in
+// turns, thanks to the lock
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let lock = File::create(sibling(path, ".lock"))?;
+    lock.lock_exclusive()?;
+
+    let temp = temp_sibling(path);
+    write_temp(&temp, contents)?;
+    fs::rename(&temp, path).map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        err
+    })
+    // The lock is released when the file is closed
+}
+
+fn write_temp(temp: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = File::create(temp)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
 
 type Result<T, E = DiskError> = std::result::Result<T, E>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OnDiskConfig<'a> {
+    // Files from before this was added don't have it, so are version 0
+    #[serde(default)]
+    pub version: u32,
     pub api_key: Cow<'a, str>,
+    #[serde(default = "OnDiskConfig::default_min_search_length")]
+    pub min_search_length: usize,
+    #[serde(default)]
+    pub tui: TuiConfig,
+    // How long fetched entries are kept for, 0 disables the cache
+    #[serde(default = "OnDiskConfig::default_cache_days")]
+    pub cache_days: u64,
+    // Most recent first, offered when no search term is given
+    #[serde(default)]
+    pub recent_searches: Vec<String>,
+    // Used instead of the built-in defaults when --type, --format, or
+    // --results aren't given
+    #[serde(default)]
+    pub default_media_type: Option<MediaType>,
+    #[serde(default)]
+    pub default_format: Option<OutputFormat>,
+    #[serde(default)]
+    pub default_results: Option<usize>,
+    // Used in order once OMDb says the API key has reached its daily limit
+    #[serde(default)]
+    pub backup_api_keys: Vec<String>,
+    // How many requests can be made each day before imdb-id refuses to make
+    // any more, 0 disables the limit
+    #[serde(default = "OnDiskConfig::default_request_budget")]
+    pub request_budget: u32,
+    // When OMDb last accepted the API key, in seconds since 1970
+    #[serde(default)]
+    pub last_validated: Option<u64>,
+}
+
+impl Default for OnDiskConfig<'_> {
+    fn default() -> Self {
+        OnDiskConfig {
+            version: OnDiskConfig::CURRENT_VERSION,
+            api_key: Cow::default(),
+            min_search_length: OnDiskConfig::DEFAULT_MIN_SEARCH_LENGTH,
+            tui: TuiConfig::default(),
+            cache_days: OnDiskConfig::DEFAULT_CACHE_DAYS,
+            recent_searches: Vec::new(),
+            default_media_type: None,
+            default_format: None,
+            default_results: None,
+            backup_api_keys: Vec::new(),
+            request_budget: OnDiskConfig::DEFAULT_REQUEST_BUDGET,
+            last_validated: None,
+        }
+    }
+}
+
+// Settings for the TUI, kept in their own section of the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TuiConfig {
+    // Show the IMDb rating as a row of stars, e.g. ★★★★☆ 8.2
+    #[serde(default)]
+    pub star_rating: bool,
+    // How each search result is shown in the list, e.g. "{title} ({year})"
+    // Fields available are title, year, type, and id
+    #[serde(default)]
+    pub list_format: Option<String>,
 }
 
 impl<'a> OnDiskConfig<'a> {
+    pub const DEFAULT_MIN_SEARCH_LENGTH: usize = 2;
+    pub const CURRENT_VERSION: u32 = 1;
+    pub const DEFAULT_CACHE_DAYS: u64 = 7;
+    pub const MAX_RECENT_SEARCHES: usize = 20;
+    // OMDb's limit for free API keys
+    pub const DEFAULT_REQUEST_BUDGET: u32 = 1000;
+    // How long the API key is trusted for without checking it again
+    pub const VALIDATION_SECS: u64 = 60 * 60 * 24;
+
+    fn default_cache_days() -> u64 {
+        OnDiskConfig::DEFAULT_CACHE_DAYS
+    }
+
+    fn default_request_budget() -> u32 {
+        OnDiskConfig::DEFAULT_REQUEST_BUDGET
+    }
+
+    fn default_min_search_length() -> usize {
+        OnDiskConfig::DEFAULT_MIN_SEARCH_LENGTH
+    }
+
+    // Whether OMDb accepted the API key recently enough (at `now`, in seconds
+    // since 1970) that it doesn't need checking again. Times in the future
+    // mean the clock has changed, so aren't trusted
+    pub fn recently_validated(&self, now: u64) -> bool {
+        match self.last_validated {
+            Some(at) if at <= now => now - at < OnDiskConfig::VALIDATION_SECS,
+            _ => false,
+        }
+    }
+
+    // Puts the search term at the front of the recent searches, moving it
+    // there if it's already in them. Returns whether anything changed
+    pub fn remember_search(&mut self, search_term: &str) -> bool {
+        if self.recent_searches.first().map(String::as_str) == Some(search_term)
+        {
+            return false;
+        }
+        self.recent_searches.retain(|recent| recent != search_term);
+        self.recent_searches.insert(0, search_term.to_owned());
+        self.recent_searches
+            .truncate(OnDiskConfig::MAX_RECENT_SEARCHES);
+        true
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    pub fn save(&self) -> Result<()> {
+        self.save_to(config_path())
+    }
+
+    #[cfg(feature = "keyring")]
     pub fn save(&self) -> Result<()> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(CONFIG_PATH.as_path())
-            .map_err(DiskError::Write)?;
+        self.save_to_keyring(config_path(), &OsKeyring)
+    }
 
+    fn save_to(&self, path: &Path) -> Result<()> {
+        create_parent(path).map_err(DiskError::Write)?;
         let ser = serde_json::to_string_pretty(&self)
             .map_err(DiskError::Serialise)?;
-        file.write_all(ser.as_bytes()).map_err(DiskError::Write)
+        write_atomically(path, ser.as_bytes()).map_err(DiskError::Write)
     }
 
+    #[cfg(not(feature = "keyring"))]
     pub fn load() -> Result<Self> {
-        let file =
-            File::open(CONFIG_PATH.as_path()).map_err(|err| {
-                match err.kind() {
-                    io::ErrorKind::NotFound => {
-                        DiskError::NotFound(CONFIG_PATH.to_string_lossy())
-                    },
-                    _ => DiskError::Write(err),
-                }
-            })?;
+        OnDiskConfig::load_from(config_path())
+    }
+
+    #[cfg(feature = "keyring")]
+    pub fn load() -> Result<Self> {
+        OnDiskConfig::load_from_keyring(config_path(), &OsKeyring)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let path_lossy = || Cow::Owned(path.to_string_lossy().into_owned());
+        let file = File::open(path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => DiskError::NotFound(path_lossy()),
+            _ => DiskError::Read(err),
+        })?;
         let config =
             serde_json::from_reader(BufReader::new(file)).map_err(|err| {
-                DiskError::Deserialise(err, CONFIG_PATH.to_string_lossy())
+                // Moved out of the way, so it's only complained about once and
+                // the next save starts afresh
+                let backup = sibling(path, ".bak");
+                match fs::rename(path, &backup) {
+                    Ok(()) => log::warn!(
+                        "the config file has been moved to {}, a new one will \
+                        be saved",
+                        backup.display(),
+                    ),
+                    Err(why) => log::warn!(
+                        "couldn't move the config file out of the way: {why}"
+                    ),
+                }
+                DiskError::Deserialise(err, path_lossy())
             })?;
         Ok(config)
     }
+
+    // The API key goes in the keyring, and everything else in the file. If
+    // the keyring can't be used, the key goes in the file rather than being
+    // lost
+    #[cfg(any(feature = "keyring", test))]
+    fn save_to_keyring(
+        &self,
+        path: &Path,
+        keyring: &dyn Keyring,
+    ) -> Result<()> {
+        if !self.api_key.is_empty() {
+            if let Err(why) = keyring.set(&self.api_key) {
+                log::warn!("{why}, saving the API key in the config file");
+                return self.save_to(path);
+            }
+        }
+        let without_key = OnDiskConfig {
+            api_key: Cow::Borrowed(""),
+            tui: self.tui.clone(),
+            recent_searches: self.recent_searches.clone(),
+            backup_api_keys: self.backup_api_keys.clone(),
+            ..*self
+        };
+        without_key.save_to(path)
+    }
+
+    // Prefers the key from the keyring. If there isn't one, the key from the
+    // file is moved into the keyring. The file's key is used if the keyring
+    // can't be, e.g. when there's no secret service running
+    #[cfg(any(feature = "keyring", test))]
+    fn load_from_keyring(path: &Path, keyring: &dyn Keyring) -> Result<Self> {
+        let mut config = OnDiskConfig::load_from(path)?;
+        match keyring.get() {
+            Ok(Some(api_key)) => config.api_key = Cow::Owned(api_key),
+            Ok(None) if !config.api_key.is_empty() => {
+                config.save_to_keyring(path, keyring)?
+            },
+            Ok(None) => {},
+            Err(why) => {
+                log::warn!("{why}, using the API key in the config file")
+            },
+        }
+        Ok(config)
+    }
+
+    // Upgrades a config loaded from an older version of imdb-id, returning
+    // whether anything changed (and so should be saved)
+    pub fn migrate(&mut self) -> bool {
+        if self.version > OnDiskConfig::CURRENT_VERSION {
+            log::warn!(
+                "your config is from a newer version of imdb-id \
+                (version {}), so some settings may be ignored",
+                self.version
+            );
+            return false;
+        }
+        let migrated = self.version < OnDiskConfig::CURRENT_VERSION;
+        // 0 -> 1: only the version was added, serde defaults cover the rest
+        self.version = OnDiskConfig::CURRENT_VERSION;
+        migrated
+    }
+}
+
+// A result bookmarked with --favourite (or f in the interactive list)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favourite {
+    pub title: String,
+    pub year: Year,
+    pub imdb_id: String,
+    pub media_type: MediaType,
+}
+
+impl From<&SearchResult> for Favourite {
+    fn from(sr: &SearchResult) -> Self {
+        Favourite {
+            title: sr.title.clone(),
+            year: sr.year.clone(),
+            imdb_id: sr.imdb_id.clone(),
+            media_type: sr.media_type,
+        }
+    }
+}
+
+// For --list-favourites, so they can be output like search results
+impl From<Favourite> for SearchResult {
+    fn from(favourite: Favourite) -> Self {
+        SearchResult {
+            title: favourite.title,
+            year: favourite.year,
+            imdb_id: favourite.imdb_id,
+            media_type: favourite.media_type,
+            poster: None,
+        }
+    }
+}
+
+// In the order they were added, without duplicates
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Favourites(pub Vec<Favourite>);
+
+impl Favourites {
+    pub fn load() -> Result<Self> {
+        Favourites::load_from(&favourites_path())
+    }
+
+    // Having no favourites yet isn't an error
+    fn load_from(path: &Path) -> Result<Self> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(Favourites::default())
+            },
+            Err(err) => return Err(DiskError::FavouritesRead(err)),
+        };
+        serde_json::from_reader(BufReader::new(file)).map_err(|err| {
+            let path = Cow::Owned(path.to_string_lossy().into_owned());
+            DiskError::FavouritesDeserialise(err, path)
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&favourites_path())
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let ser =
+            serde_json::to_string_pretty(self).map_err(DiskError::Serialise)?;
+        create_parent(path).map_err(DiskError::FavouritesWrite)?;
+        fs::write(path, ser).map_err(DiskError::FavouritesWrite)
+    }
+
+    // Returns whether it was added, i.e. it wasn't already a favourite
+    pub fn insert(&mut self, search_result: &SearchResult) -> bool {
+        if self
+            .0
+            .iter()
+            .any(|fav| fav.imdb_id == search_result.imdb_id)
+        {
+            return false;
+        }
+        self.0.push(Favourite::from(search_result));
+        true
+    }
+
+    // Loads the favourites, adds to them, and saves them again
+    pub fn add(search_result: &SearchResult) -> Result<bool> {
+        let mut favourites = Favourites::load()?;
+        let added = favourites.insert(search_result);
+        if added {
+            favourites.save()?;
+        }
+        Ok(added)
+    }
+}
+
+// How many requests have been made to OMDb on the date given (in UTC, as
+// YYYY-MM-DD), kept between runs so the daily limit isn't gone over
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestCount {
+    pub date: String,
+    pub count: u32,
+}
+
+impl RequestCount {
+    pub fn load() -> Result<Self> {
+        RequestCount::load_from(&request_count_path())
+    }
+
+    // Not having made any requests yet isn't an error
+    fn load_from(path: &Path) -> Result<Self> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(RequestCount::default())
+            },
+            Err(err) => return Err(DiskError::RequestCountRead(err)),
+        };
+        serde_json::from_reader(BufReader::new(file)).map_err(|err| {
+            let path = Cow::Owned(path.to_string_lossy().into_owned());
+            DiskError::RequestCountDeserialise(err, path)
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&request_count_path())
+    }
+
+    fn save_to(&self, path: &Path) -> Result<()> {
+        let ser = serde_json::to_string(self).map_err(DiskError::Serialise)?;
+        create_parent(path).map_err(DiskError::RequestCountWrite)?;
+        // Saved after every request, so often enough to be mid-write when
+        // another run is too
+        write_atomically(path, ser.as_bytes())
+            .map_err(DiskError::RequestCountWrite)
+    }
+
+    // How many requests have been made on the date given, which is none if
+    // the count is from a previous day
+    pub fn on(&self, date: &str) -> u32 {
+        if self.date == date {
+            self.count
+        } else {
+            0
+        }
+    }
+
+    // Counts a request made on the date given, starting again from 0 if it's
+    // a new day. Returns the count after adding it
+    pub fn add(&mut self, date: &str) -> u32 {
+        if self.date != date {
+            self.date = date.to_owned();
+            self.count = 0;
+        }
+        self.count += 1;
+        self.count
+    }
+}
+
+// A marker left when saving the config fails, so that the user is only told
+// what to do about it once it's clear the config can't be saved. It's kept
+// in the cache directory, as the config's own directory is usually the
+// problem
+#[derive(Debug)]
+pub struct SaveFailures {
+    marker: PathBuf,
+    // The config can be saved a few times in one run, but a failure is only
+    // worth warning about once
+    failed_this_run: Cell<bool>,
+}
+
+impl SaveFailures {
+    pub fn new() -> Self {
+        let dir = dirs::cache_dir().unwrap_or_else(env::temp_dir);
+        SaveFailures::at(dir.join("imdb-id-save-failed"))
+    }
+
+    fn at(marker: PathBuf) -> Self {
+        SaveFailures {
+            marker,
+            failed_this_run: Cell::new(false),
+        }
+    }
+
+    // Records whether the latest save failed. None if there's nothing to warn
+    // about, because it didn't fail or a save already failed this run.
+    // Otherwise, whether saving failed on an earlier run too. If the marker
+    // can't be written either, every failure is treated as a repeat
+    pub fn record(&self, failed: bool) -> Option<bool> {
+        if !failed {
+            let _ = fs::remove_file(&self.marker);
+            return None;
+        }
+        if self.failed_this_run.replace(true) {
+            return None;
+        }
+        let failed_before = self.marker.exists();
+        let written = create_parent(&self.marker)
+            .and_then(|_| fs::write(&self.marker, ""))
+            .is_ok();
+        Some(failed_before || !written)
+    }
+}
+
+impl Default for SaveFailures {
+    fn default() -> Self {
+        SaveFailures::new()
+    }
+}
+
+// Seconds since 1970-01-01, in UTC
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+// e.g. "2024-03-01"
+const DATE_FORMAT: &[FormatItem] = format_description!("[year]-[month]-[day]");
+
+// Today's date in UTC, as YYYY-MM-DD
+pub fn today() -> String {
+    OffsetDateTime::now_utc()
+        .date()
+        .format(DATE_FORMAT)
+        .expect("dates can always be formatted as YYYY-MM-DD")
+}
+
+// Responses from OMDb for individual entries, one file per IMDb ID
+#[derive(Debug)]
+pub struct EntryCache {
+    dir: PathBuf,
+    max_age: Duration,
+    // Whether to ignore what's already cached (but still update it)
+    refresh: bool,
+}
+
+impl EntryCache {
+    const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+    pub fn new(days: u64, refresh: bool) -> Self {
+        EntryCache::in_dir(cache_dir(), days, refresh)
+    }
+
+    pub(crate) fn in_dir(dir: PathBuf, days: u64, refresh: bool) -> Self {
+        EntryCache {
+            dir,
+            max_age: EntryCache::DAY * days as u32,
+            refresh,
+        }
+    }
+
+    fn path(&self, imdb_id: &str) -> PathBuf {
+        self.dir.join(format!("{imdb_id}.json"))
+    }
+
+    // None if there's nothing cached, or it's too old
+    pub fn get(&self, imdb_id: &str) -> Result<Option<String>> {
+        if self.refresh {
+            return Ok(None);
+        }
+        let path = self.path(imdb_id);
+        let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(None)
+            },
+            Err(err) => return Err(DiskError::Cache(err)),
+        };
+        // Clock changes can make this fail, in which case it's not trusted
+        match modified.elapsed() {
+            Ok(age) if age < self.max_age => {},
+            _ => return Ok(None),
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .map_err(DiskError::Cache)
+    }
+
+    pub fn put(&self, imdb_id: &str, body: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir).map_err(DiskError::Cache)?;
+        fs::write(self.path(imdb_id), body).map_err(DiskError::Cache)
+    }
+}
+
+#[cfg(any(feature = "keyring", test))]
+trait Keyring {
+    fn get(&self) -> Result<Option<String>>;
+    fn set(&self, api_key: &str) -> Result<()>;
+}
+
+#[cfg(feature = "keyring")]
+struct OsKeyring;
+
+#[cfg(feature = "keyring")]
+impl OsKeyring {
+    const SERVICE: &'static str = "imdb-id";
+    const USERNAME: &'static str = "omdb";
+
+    // Each profile's key is kept separately
+    fn entry() -> keyring::Result<keyring::Entry> {
+        let username = match PROFILE.get() {
+            Some(profile) => format!("{}-{profile}", OsKeyring::USERNAME),
+            None => OsKeyring::USERNAME.to_owned(),
+        };
+        keyring::Entry::new(OsKeyring::SERVICE, &username)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl Keyring for OsKeyring {
+    fn get(&self) -> Result<Option<String>> {
+        match OsKeyring::entry().and_then(|entry| entry.get_password()) {
+            Ok(api_key) => Ok(Some(api_key)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(DiskError::Keyring(err.to_string())),
+        }
+    }
+
+    fn set(&self, api_key: &str) -> Result<()> {
+        OsKeyring::entry()
+            .and_then(|entry| entry.set_password(api_key))
+            .map_err(|err| DiskError::Keyring(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fs;
+
+    #[derive(Default)]
+    struct MemoryKeyring(RefCell<Option<String>>);
+
+    impl Keyring for MemoryKeyring {
+        fn get(&self) -> Result<Option<String>> {
+            Ok(self.0.borrow().clone())
+        }
+
+        fn set(&self, api_key: &str) -> Result<()> {
+            *self.0.borrow_mut() = Some(api_key.to_owned());
+            Ok(())
+        }
+    }
+
+    // Like having no secret service to talk to
+    struct BrokenKeyring;
+
+    impl Keyring for BrokenKeyring {
+        fn get(&self) -> Result<Option<String>> {
+            Err(DiskError::Keyring("no secret service".into()))
+        }
+
+        fn set(&self, _: &str) -> Result<()> {
+            Err(DiskError::Keyring("no secret service".into()))
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("imdb-id-{}-{name}", std::process::id()));
+        path
+    }
+
+    #[test]
+    fn round_trip() {
+        let path = temp_path("config.json");
+        let config = OnDiskConfig {
+            api_key: "123abc".into(),
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+        let loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.api_key, "123abc");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn defaults_round_trip() {
+        let path = temp_path("defaults-config.json");
+        let config = OnDiskConfig {
+            api_key: "123abc".into(),
+            default_media_type: Some(MediaType::SERIES),
+            default_format: Some(OutputFormat::Csv),
+            default_results: Some(5),
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+        let loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.default_media_type, Some(MediaType::SERIES));
+        assert_eq!(loaded.default_format, Some(OutputFormat::Csv));
+        assert_eq!(loaded.default_results, Some(5));
+        fs::remove_file(&path).unwrap();
+
+        // Files from before these were added don't have them
+        let old: OnDiskConfig =
+            serde_json::from_str(r#"{"version":1,"api_key":"123abc"}"#)
+                .unwrap();
+        assert_eq!(old.default_format, None);
+        let edited: OnDiskConfig = serde_json::from_str(
+            r#"{"api_key":"123abc","default_media_type":"movie","default_format":"ndjson"}"#,
+        )
+        .unwrap();
+        assert_eq!(edited.default_media_type, Some(MediaType::MOVIE));
+        assert_eq!(edited.default_format, Some(OutputFormat::NdJson));
+    }
+
+    #[test]
+    fn config_path_from_env() {
+        let path = temp_path("env-config.json");
+        env::set_var("IMDB_ID_CONFIG", &path);
+        let resolved = resolve_config_path(env::var_os("IMDB_ID_CONFIG"));
+        env::remove_var("IMDB_ID_CONFIG");
+        assert_eq!(resolved, path);
+
+        let config = OnDiskConfig {
+            api_key: "123abc".into(),
+            ..Default::default()
+        };
+        config.save_to(&resolved).unwrap();
+        let loaded = OnDiskConfig::load_from(&resolved).unwrap();
+        assert_eq!(loaded.api_key, "123abc");
+        fs::remove_file(&resolved).unwrap();
+
+        // The error says where it looked
+        match OnDiskConfig::load_from(&resolved) {
+            Err(DiskError::NotFound(shown)) => {
+                assert_eq!(shown, path.to_string_lossy())
+            },
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+
+        // Relative paths would depend on where imdb-id is run from
+        let fallback = resolve_config_path(Some("imdb-id.json".into()));
+        assert!(fallback.is_absolute());
+    }
+
+    #[test]
+    fn migrate_old_config() {
+        let path = temp_path("old-config.json");
+        fs::write(&path, r#"{"api_key":"123abc"}"#).unwrap();
+        let mut loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.version, 0);
+        assert!(loaded.migrate());
+        assert_eq!(loaded.version, OnDiskConfig::CURRENT_VERSION);
+        assert_eq!(loaded.api_key, "123abc");
+        assert_eq!(
+            loaded.min_search_length,
+            OnDiskConfig::DEFAULT_MIN_SEARCH_LENGTH
+        );
+
+        // Once saved, there's nothing more to do
+        loaded.save_to(&path).unwrap();
+        let mut reloaded = OnDiskConfig::load_from(&path).unwrap();
+        assert!(!reloaded.migrate());
+        assert_eq!(reloaded.api_key, "123abc");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keyring_round_trip() {
+        let path = temp_path("keyring-config.json");
+        let keyring = MemoryKeyring::default();
+        let config = OnDiskConfig {
+            api_key: "123abc".into(),
+            ..Default::default()
+        };
+        config.save_to_keyring(&path, &keyring).unwrap();
+        assert_eq!(keyring.get().unwrap().as_deref(), Some("123abc"));
+        assert!(!fs::read_to_string(&path).unwrap().contains("123abc"));
+
+        let loaded = OnDiskConfig::load_from_keyring(&path, &keyring).unwrap();
+        assert_eq!(loaded.api_key, "123abc");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recent_searches() {
+        let mut config = OnDiskConfig::default();
+        assert!(config.remember_search("up"));
+        assert!(config.remember_search("1917"));
+        assert_eq!(config.recent_searches, ["1917", "up"]);
+
+        // Searching for the same thing again doesn't change anything
+        assert!(!config.remember_search("1917"));
+        // An older search moves back to the front instead of repeating
+        assert!(config.remember_search("up"));
+        assert_eq!(config.recent_searches, ["up", "1917"]);
+
+        // The oldest ones are forgotten
+        for n in 0..OnDiskConfig::MAX_RECENT_SEARCHES {
+            config.remember_search(&n.to_string());
+        }
+        assert_eq!(
+            config.recent_searches.len(),
+            OnDiskConfig::MAX_RECENT_SEARCHES,
+        );
+        assert_eq!(config.recent_searches[0], "19");
+        assert!(!config.recent_searches.contains(&String::from("up")));
+    }
+
+    #[test]
+    fn favourites() {
+        let result = |imdb_id: &str, title: &str| SearchResult {
+            title: title.into(),
+            year: "2014".parse().unwrap(),
+            imdb_id: imdb_id.into(),
+            media_type: MediaType::MOVIE,
+            poster: Some("https://example.com/poster.jpg".into()),
+        };
+        let path = temp_path("favourites.json");
+        let _ = fs::remove_file(&path);
+
+        // Nothing saved yet
+        let mut favourites = Favourites::load_from(&path).unwrap();
+        assert!(favourites.0.is_empty());
+
+        assert!(favourites.insert(&result("tt2802144", "Kingsman")));
+        assert!(favourites.insert(&result("tt1049413", "Up")));
+        // Already there, even with a different title
+        assert!(!favourites.insert(&result("tt2802144", "Kingsman 2")));
+        assert_eq!(favourites.0.len(), 2);
+
+        favourites.save_to(&path).unwrap();
+        let loaded = Favourites::load_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded
+            .0
+            .into_iter()
+            .map(SearchResult::from)
+            .map(|sr| (sr.imdb_id, sr.title, sr.year.to_string(), sr.poster))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            loaded,
+            [
+                ("tt2802144".into(), "Kingsman".into(), "2014".into(), None),
+                ("tt1049413".into(), "Up".into(), "2014".into(), None),
+            ],
+        );
+    }
+
+    #[test]
+    fn keyring_migration() {
+        let path = temp_path("plaintext-config.json");
+        let keyring = MemoryKeyring::default();
+        OnDiskConfig {
+            api_key: "123abc".into(),
+            ..Default::default()
+        }
+        .save_to(&path)
+        .unwrap();
+
+        // The key is moved out of the file on the first load
+        let loaded = OnDiskConfig::load_from_keyring(&path, &keyring).unwrap();
+        assert_eq!(loaded.api_key, "123abc");
+        assert_eq!(keyring.get().unwrap().as_deref(), Some("123abc"));
+        let on_disk = OnDiskConfig::load_from(&path).unwrap();
+        assert!(on_disk.api_key.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keyring_unavailable() {
+        let path = temp_path("no-keyring-config.json");
+        let config = OnDiskConfig {
+            api_key: "123abc".into(),
+            ..Default::default()
+        };
+        // The key is kept in the file instead
+        config.save_to_keyring(&path, &BrokenKeyring).unwrap();
+        assert_eq!(OnDiskConfig::load_from(&path).unwrap().api_key, "123abc");
+
+        let loaded =
+            OnDiskConfig::load_from_keyring(&path, &BrokenKeyring).unwrap();
+        assert_eq!(loaded.api_key, "123abc");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn entry_cache_expiry() {
+        let dir = temp_path("cache-expiry");
+        let cache = EntryCache::in_dir(dir.clone(), 1, false);
+        assert_eq!(cache.get("tt1049413").unwrap(), None);
+        cache.put("tt1049413", "{}").unwrap();
+        assert_eq!(cache.get("tt1049413").unwrap().as_deref(), Some("{}"));
+
+        // Everything is too old with no days to live
+        let expired = EntryCache::in_dir(dir.clone(), 0, false);
+        assert_eq!(expired.get("tt1049413").unwrap(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn request_count_rollover() {
+        let path = temp_path("requests.json");
+        assert_eq!(RequestCount::load_from(&path).unwrap().on("2024-03-01"), 0);
+        let mut count = RequestCount::default();
+        assert_eq!(count.add("2024-03-01"), 1);
+        assert_eq!(count.add("2024-03-01"), 2);
+        count.save_to(&path).unwrap();
+
+        let mut loaded = RequestCount::load_from(&path).unwrap();
+        assert_eq!(loaded, count);
+        // Past midnight, it starts again
+        assert_eq!(loaded.on("2024-03-02"), 0);
+        assert_eq!(loaded.add("2024-03-02"), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_failure() {
+        // The parent is a file, so it can't be made into a directory
+        let file = temp_path("not-a-dir");
+        fs::write(&file, "").unwrap();
+        let path = file.join("config.json");
+        let err = OnDiskConfig::default().save_to(&path).unwrap_err();
+        assert!(matches!(err, DiskError::Write(_)), "got {err:?}");
+        assert!(err.hint().is_some());
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn repeated_save_failure() {
+        let marker = temp_path("save-failed");
+        // Each run has its own SaveFailures
+        let run = || SaveFailures::at(marker.clone());
+        let first = run();
+        assert_eq!(first.record(true), Some(false));
+        // Only warned about once a run
+        assert_eq!(first.record(true), None);
+
+        let second = run();
+        assert_eq!(second.record(true), Some(true));
+        assert_eq!(second.record(true), None);
+
+        // Saving successfully forgets about the failures
+        let third = run();
+        assert_eq!(third.record(false), None);
+        assert!(!marker.exists());
+        assert_eq!(run().record(true), Some(false));
+        run().record(false);
+    }
+
+    #[test]
+    fn interrupted_save() {
+        let dir = temp_path("interrupted");
+        let path = dir.join("config.json");
+        let original = OnDiskConfig {
+            api_key: "abc123".into(),
+            ..Default::default()
+        };
+        original.save_to(&path).unwrap();
+
+        // Stopping after writing, before it's moved into place
+        write_temp(&temp_sibling(&path), b"{\"api_key\": \"de").unwrap();
+        let loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.api_key, "abc123");
+
+        // The next save isn't bothered by what was left behind
+        let replacement = OnDiskConfig {
+            api_key: "def456".into(),
+            ..Default::default()
+        };
+        replacement.save_to(&path).unwrap();
+        let loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.api_key, "def456");
+        assert!(!temp_sibling(&path).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_creates_directories() {
+        let dir = temp_path("fresh");
+        let path = dir.join("imdb-id").join("nested").join("config.json");
+        let config = OnDiskConfig {
+            api_key: "abc123".into(),
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+        let loaded = OnDiskConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.api_key, "abc123");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_corrupt() {
+        let dir = temp_path("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("imdb-id.json");
+        fs::write(&path, r#"{"api_key": "abc"#).unwrap();
+        let err = OnDiskConfig::load_from(&path).unwrap_err();
+        assert!(matches!(err, DiskError::Deserialise(..)), "got {err:?}");
+
+        // Kept for the user to look at, but out of the way
+        assert!(!path.exists());
+        let backup = dir.join("imdb-id.json.bak");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), r#"{"api_key": "abc"#);
+        let err = OnDiskConfig::load_from(&path).unwrap_err();
+        assert!(matches!(err, DiskError::NotFound(_)), "got {err:?}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn profiles() {
+        let dir = temp_path("profiles");
+        let default = dir.join("imdb-id.json");
+        let work = profile_path(&default, "work");
+        let personal = profile_path(&default, "personal");
+        assert_eq!(work, dir.join("imdb-id.work.json"));
+        assert_eq!(personal, dir.join("imdb-id.personal.json"));
+
+        let work_config = OnDiskConfig {
+            api_key: "abc123".into(),
+            default_media_type: Some(MediaType::SERIES),
+            ..Default::default()
+        };
+        let personal_config = OnDiskConfig {
+            api_key: "def456".into(),
+            default_results: Some(5),
+            ..Default::default()
+        };
+        work_config.save_to(&work).unwrap();
+        personal_config.save_to(&personal).unwrap();
+
+        let loaded = OnDiskConfig::load_from(&work).unwrap();
+        assert_eq!(loaded.api_key, "abc123");
+        assert_eq!(loaded.default_media_type, Some(MediaType::SERIES));
+        assert_eq!(loaded.default_results, None);
+        let loaded = OnDiskConfig::load_from(&personal).unwrap();
+        assert_eq!(loaded.api_key, "def456");
+        assert_eq!(loaded.default_media_type, None);
+        assert_eq!(loaded.default_results, Some(5));
+        // Neither touches the default profile
+        assert!(!default.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn validation_freshness() {
+        let now = 1_700_000_000;
+        let validated_at = |last_validated| OnDiskConfig {
+            last_validated,
+            ..Default::default()
+        };
+        assert!(!validated_at(None).recently_validated(now));
+        assert!(validated_at(Some(now)).recently_validated(now));
+        assert!(validated_at(Some(now - 60 * 60)).recently_validated(now));
+        let day = OnDiskConfig::VALIDATION_SECS;
+        assert!(validated_at(Some(now - day + 1)).recently_validated(now));
+        assert!(!validated_at(Some(now - day)).recently_validated(now));
+        assert!(!validated_at(Some(now - day * 30)).recently_validated(now));
+        // The clock has gone backwards since
+        assert!(!validated_at(Some(now + 60)).recently_validated(now));
+
+        // Older config files don't have it
+        let config =
+            serde_json::from_str::<OnDiskConfig>(r#"{"api_key": "abc123"}"#)
+                .unwrap();
+        assert_eq!(config.last_validated, None);
+    }
+
+    #[test]
+    fn load_missing() {
+        let path = temp_path("does-not-exist.json");
+        let err = OnDiskConfig::load_from(&path).unwrap_err();
+        assert!(matches!(err, DiskError::NotFound(_)), "got {err:?}");
+        assert!(err.hint().is_none());
+    }
 }
@@ -3,12 +3,11 @@ use crate::InteractivityError;
 
 pub mod cli {
     use super::InteractivityError;
-    use crate::omdb::{test_api_key, MediaType};
-    use crate::{FinalError, MaybeFatal, SignUpError};
+    use crate::omdb::{get, test_api_key, MediaType};
+    use crate::{FinalError, MaybeFatal, SignUpError, Year};
     use dialoguer::theme::ColorfulTheme;
-    use dialoguer::{Confirm, Input};
+    use dialoguer::{Confirm, Input, Select};
     use lazy_regex::{lazy_regex, Regex};
-    use minreq::get;
     use once_cell::sync::Lazy;
     use std::ops::Deref;
 
@@ -112,23 +111,78 @@ pub mod cli {
         }
     }
 
+    // Offers the recent searches (if there are any) before asking for a new one
     pub fn get_search_term(
         types: MediaType,
+        min_length: usize,
+        recent: &[String],
     ) -> Result<String, InteractivityError> {
-        let question = Input::with_theme(THEME.deref())
+        if !recent.is_empty() {
+            let something_else = "Search for something else…";
+            let choice = Select::with_theme(THEME.deref())
+                .with_prompt(format!("Which {types} are you looking for?"))
+                .items(recent)
+                .item(something_else)
+                .default(0)
+                .interact()?;
+            if let Some(search_term) = recent.get(choice) {
+                return Ok(search_term.clone());
+            }
+        }
+        let question = Input::<String>::with_theme(THEME.deref())
             .with_prompt(format!(
                 "Please enter the name of the {types} you're looking for"
             ))
+            .validate_with(|term: &String| {
+                match term.trim().chars().count() >= min_length {
+                    true => Ok(()),
+                    false => Err(format!(
+                        "Search term must be at least {min_length} characters long, try being more specific"
+                    )),
+                }
+            })
             .interact_text()?;
         Ok(question)
     }
+
+    // For when a search is too broad to give any results. Nothing entered
+    // means the search isn't narrowed
+    pub fn get_year_filter() -> Result<Option<Year>, InteractivityError> {
+        let answer = Input::<String>::with_theme(THEME.deref())
+            .with_prompt(
+                "Which year (or range of years) should the search be narrowed to? \
+                Leave blank to give up",
+            )
+            .allow_empty(true)
+            .validate_with(|years: &String| match years.trim() {
+                "" => Ok(()),
+                years => years
+                    .parse::<Year>()
+                    .map(drop)
+                    .map_err(|why| why.to_string()),
+            })
+            .interact_text()?;
+        match answer.trim() {
+            "" => Ok(None),
+            years => Ok(years.parse().ok()),
+        }
+    }
 }
 
 pub mod tui {
     use super::InteractivityError;
-    use crate::omdb::{get_entry, Entry};
-    use crate::{RequestError, SearchResult};
-    use crossterm::event::{Event, KeyCode};
+    use crate::omdb::{
+        get_entry_plot, get_season, Awards, Entry, Episode, Matches,
+        RatingSource, RequestBundle, SearchResult, Season,
+    };
+    use crate::{
+        DiskError, EmitNonFatal, Favourites, ListFormatParseError, OmdbError,
+        RequestError, TuiConfig, WEB_URL,
+    };
+    use crossterm::event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent,
+        KeyModifiers, MouseButton, MouseEventKind,
+    };
     use crossterm::terminal::{
         disable_raw_mode, enable_raw_mode, EnterAlternateScreen,
         LeaveAlternateScreen,
@@ -137,19 +191,32 @@ pub mod tui {
     use itertools::Itertools;
     use once_cell::sync::Lazy;
     use ratatui::backend::CrosstermBackend;
-    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
     use ratatui::style::{Modifier, Style};
     use ratatui::text::{Line, Span};
     use ratatui::widgets::{
         Block, Borders, List, ListItem, ListState, Paragraph, Wrap,
     };
-    use ratatui::Terminal;
-    use std::fmt::Display;
+    use ratatui::{Frame, Terminal};
+    use std::collections::HashMap;
+    use std::fmt::{Display, Write};
     use std::io;
-    use std::io::Stdout;
+    use std::panic;
+    use std::str::FromStr;
+    use std::sync::{mpsc, Arc, Condvar, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use time::macros::format_description;
+    use time::Date;
 
     const HIGHLIGHT_SYMBOL: &str = "> ";
     const MIN_MARGIN: usize = 1;
+    const STARS: usize = 5;
+    // The longest gap between two clicks on a result that picks it
+    const DOUBLE_CLICK: Duration = Duration::from_millis(500);
+    // How long the selection has to stay put before its entry is fetched
+    const SETTLE: Duration = Duration::from_millis(150);
+    const FOCUSED_INFORMATION: &str = "[Information] (Tab: back to the list)";
 
     static BOLD: Lazy<Style> =
         Lazy::new(|| Style::default().add_modifier(Modifier::BOLD));
@@ -160,11 +227,18 @@ pub mod tui {
     }
 
     impl ListItemList {
-        pub fn new<T: Display>(items: &[T], width: usize) -> Self {
+        pub fn new(
+            items: &[SearchResult],
+            width: usize,
+            format: Option<&ListFormat>,
+        ) -> Self {
             let items = items
                 .iter()
-                .map(|t| {
-                    let mut s = t.to_string();
+                .map(|sr| {
+                    let mut s = match format {
+                        Some(format) => format.apply(sr),
+                        None => sr.list_label(),
+                    };
                     textwrap::fill_inplace(&mut s, width);
                     ListItem::new(s)
                 })
@@ -178,15 +252,112 @@ pub mod tui {
         }
     }
 
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    enum ListField {
+        Title,
+        Year,
+        Type,
+        Id,
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    enum ListFormatPart {
+        Literal(String),
+        Field(ListField),
+    }
+
+    // A template for how each search result is shown in the list, set by
+    // tui.list_format in the config, e.g. "{year} — {title} [{type}]"
+    // Braces can be escaped by doubling them, i.e. "{{" and "}}"
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct ListFormat(Vec<ListFormatPart>);
+
+    impl ListFormat {
+        fn apply(&self, sr: &SearchResult) -> String {
+            let mut buf = String::new();
+            for part in self.0.iter() {
+                match part {
+                    ListFormatPart::Literal(s) => buf.push_str(s),
+                    ListFormatPart::Field(field) => {
+                        use ListField::*;
+                        match field {
+                            Title => buf.push_str(&sr.title),
+                            Year => write!(buf, "{}", sr.year).unwrap(),
+                            Type => write!(buf, "{}", sr.media_type).unwrap(),
+                            Id => buf.push_str(&sr.imdb_id),
+                        }
+                    },
+                }
+            }
+            buf
+        }
+    }
+
+    impl FromStr for ListFormat {
+        type Err = ListFormatParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            use ListFormatParseError::*;
+            let mut parts = Vec::new();
+            let mut literal = String::new();
+            let mut chars = s.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '{' if chars.peek() == Some(&'{') => {
+                        chars.next();
+                        literal.push('{');
+                    },
+                    '}' if chars.peek() == Some(&'}') => {
+                        chars.next();
+                        literal.push('}');
+                    },
+                    '{' => {
+                        let mut name = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') => break,
+                                Some(c) => name.push(c),
+                                None => return Err(Unclosed),
+                            }
+                        }
+                        let field = match name.as_str() {
+                            "title" => ListField::Title,
+                            "year" => ListField::Year,
+                            "type" => ListField::Type,
+                            "id" => ListField::Id,
+                            _ => return Err(UnknownField(name)),
+                        };
+                        if !literal.is_empty() {
+                            parts.push(ListFormatPart::Literal(
+                                std::mem::take(&mut literal),
+                            ));
+                        }
+                        parts.push(ListFormatPart::Field(field));
+                    },
+                    c => literal.push(c),
+                }
+            }
+            if !literal.is_empty() {
+                parts.push(ListFormatPart::Literal(literal));
+            }
+            Ok(ListFormat(parts))
+        }
+    }
+
     struct StatefulList<'a> {
         state: ListState,
-        underlying: &'a [SearchResult],
+        config: &'a TuiConfig,
+        underlying: Vec<SearchResult>,
+        list_format: Option<ListFormat>,
         list_items: Option<ListItemList>,
         entry_paragraphs: Vec<Option<Paragraph<'static>>>,
+        // How far down the Information pane has been scrolled, reset whenever
+        // the selection changes
+        scroll: u16,
     }
 
     impl<'a> StatefulList<'a> {
-        fn new(items: &'a [SearchResult]) -> Self {
+        fn new(items: Vec<SearchResult>, config: &'a TuiConfig) -> Self {
             debug_assert!(
                 !items.is_empty(),
                 "Can't construct StatefulList without items"
@@ -194,12 +365,61 @@ pub mod tui {
             let mut state = ListState::default();
             state.select(Some(0));
 
+            // Fall back on the default if the user's format is broken
+            let list_format = config.list_format.as_deref().and_then(|s| {
+                s.parse::<ListFormat>()
+                    .map_err(|why| why.emit_unconditional())
+                    .ok()
+            });
+
             StatefulList {
                 state,
+                config,
+                entry_paragraphs: vec![None; items.len()],
                 underlying: items,
+                list_format,
                 list_items: None,
-                entry_paragraphs: vec![None; items.len()],
+                scroll: 0,
+            }
+        }
+
+        // Adds any results not already in the list, returning how many were
+        // new. Selects the first new result so the user can see them
+        fn append(
+            &mut self,
+            items: impl IntoIterator<Item = SearchResult>,
+        ) -> usize {
+            let before = self.underlying.len();
+            for sr in items {
+                if !self.underlying.iter().any(|e| e.imdb_id == sr.imdb_id) {
+                    self.underlying.push(sr);
+                }
+            }
+            let added = self.underlying.len() - before;
+            if added > 0 {
+                self.entry_paragraphs.resize(self.underlying.len(), None);
+                // Cached list items are now missing the new results
+                self.list_items = None;
+                self.select(before);
             }
+            added
+        }
+
+        // Swaps in a whole new set of results, e.g. from searching again
+        fn replace(&mut self, items: Vec<SearchResult>) {
+            debug_assert!(
+                !items.is_empty(),
+                "Can't replace StatefulList's items with nothing"
+            );
+            self.entry_paragraphs = vec![None; items.len()];
+            self.underlying = items;
+            self.list_items = None;
+            self.select(0);
+        }
+
+        fn select(&mut self, index: usize) {
+            self.state.select(Some(index));
+            self.scroll = 0;
         }
 
         fn next(&mut self) {
@@ -207,7 +427,7 @@ pub mod tui {
                 Some(index) => (index + 1) % self.underlying.len(),
                 None => 0,
             };
-            self.state.select(Some(index));
+            self.select(index);
         }
 
         fn previous(&mut self) {
@@ -217,14 +437,57 @@ pub mod tui {
                 },
                 None => 0,
             };
-            self.state.select(Some(index));
+            self.select(index);
+        }
+
+        // Like next, but by a page at a time. Only wraps around to the top
+        // from the last item
+        fn page_down(&mut self, visible: usize) {
+            let last = self.underlying.len() - 1;
+            let index = match self.state.selected() {
+                Some(index) if index == last => 0,
+                Some(index) => (index + visible.max(1)).min(last),
+                None => 0,
+            };
+            self.select(index);
+        }
+
+        // Like previous, but by a page at a time. Only wraps around to the
+        // bottom from the first item
+        fn page_up(&mut self, visible: usize) {
+            let index = match self.state.selected() {
+                Some(0) => self.underlying.len() - 1,
+                Some(index) => index.saturating_sub(visible.max(1)),
+                None => 0,
+            };
+            self.select(index);
+        }
+
+        fn first(&mut self) {
+            self.select(0);
+        }
+
+        fn last(&mut self) {
+            self.select(self.underlying.len() - 1);
+        }
+
+        fn scroll_down(&mut self, lines: u16) {
+            self.scroll = self.scroll.saturating_add(lines);
+        }
+
+        fn scroll_up(&mut self, lines: u16) {
+            self.scroll = self.scroll.saturating_sub(lines);
         }
 
         fn items(&mut self, width: usize) -> Vec<ListItem<'static>> {
             match &self.list_items {
                 Some(li) if li.width == width => li.items_cloned(),
                 _ => {
-                    let lil = ListItemList::new(self.underlying, width);
+                    let lil = ListItemList::new(
+                        &self.underlying,
+                        width,
+                        self.list_format.as_ref(),
+                    );
                     let items = lil.items_cloned();
                     self.list_items = Some(lil);
                     items
@@ -232,22 +495,49 @@ pub mod tui {
             }
         }
 
+        // How many rows each item takes up in the list as last drawn
+        fn heights(&self) -> Vec<usize> {
+            match &self.list_items {
+                Some(lil) => lil.items.iter().map(ListItem::height).collect(),
+                None => Vec::new(),
+            }
+        }
+
         fn entry(
             &mut self,
             api_key: &str,
+            full_plot: bool,
+            prefetcher: &Prefetcher,
         ) -> Result<Paragraph<'static>, RequestError> {
-            let index = self.state.selected().unwrap();
-            match &self.entry_paragraphs[index] {
-                Some(entry) => Ok(entry.clone()),
-                None => {
-                    // Make web request for entry
-                    let imdb_id = &self.underlying[index].imdb_id;
-                    let entry = get_entry(api_key, imdb_id)?;
-                    let paragraph = entry_to_paragraph(entry);
-                    self.entry_paragraphs[index] = Some(paragraph.clone());
-                    Ok(paragraph)
-                },
+            if let Some(paragraph) = self.cached_entry(prefetcher) {
+                return Ok(paragraph);
+            }
+            let index = self.current();
+            let imdb_id = &self.underlying[index].imdb_id;
+            // Make web request for entry, unless it's already being prefetched
+            let entry = match prefetcher.wait_for(imdb_id) {
+                Some(entry) => entry,
+                None => get_entry_plot(api_key, imdb_id, full_plot)?,
+            };
+            let paragraph = entry_to_paragraph(entry, self.config);
+            self.entry_paragraphs[index] = Some(paragraph.clone());
+            Ok(paragraph)
+        }
+
+        // The selected entry, if it's been fetched already (including in the
+        // background)
+        fn cached_entry(
+            &mut self,
+            prefetcher: &Prefetcher,
+        ) -> Option<Paragraph<'static>> {
+            let index = self.current();
+            if self.entry_paragraphs[index].is_none() {
+                let imdb_id = &self.underlying[index].imdb_id;
+                let entry = prefetcher.take(imdb_id)?;
+                self.entry_paragraphs[index] =
+                    Some(entry_to_paragraph(entry, self.config));
             }
+            self.entry_paragraphs[index].clone()
         }
 
         fn current(&self) -> usize {
@@ -255,26 +545,444 @@ pub mod tui {
                 .selected()
                 .expect("Stateful list should always have a selected item")
         }
+
+        // The IMDb IDs of the results either side of the selected one (going
+        // round like next and previous do) that haven't been fetched yet
+        fn neighbours(&self) -> Vec<String> {
+            let len = self.underlying.len();
+            let index = self.current();
+            let mut neighbours = Vec::with_capacity(2);
+            for neighbour in [(index + 1) % len, (index + len - 1) % len] {
+                if neighbour != index
+                    && self.entry_paragraphs[neighbour].is_none()
+                    && !neighbours.contains(&neighbour)
+                {
+                    neighbours.push(neighbour);
+                }
+            }
+            neighbours
+                .into_iter()
+                .map(|index| self.underlying[index].imdb_id.clone())
+                .collect()
+        }
+    }
+
+    // Keeps track of when the selection last moved, so entries aren't fetched
+    // for every result passed on the way when scrolling quickly
+    struct Debounce {
+        selected: usize,
+        // None once the selection has settled
+        moved: Option<Instant>,
+    }
+
+    impl Debounce {
+        fn new(selected: usize) -> Self {
+            Debounce {
+                selected,
+                moved: None,
+            }
+        }
+
+        // Whether the selection has stayed put for long enough that its entry
+        // should be fetched
+        fn settled(&mut self, selected: usize, now: Instant) -> bool {
+            if selected != self.selected {
+                self.selected = selected;
+                self.moved = Some(now);
+            }
+            match self.moved {
+                Some(moved) if now.duration_since(moved) < SETTLE => false,
+                _ => {
+                    self.moved = None;
+                    true
+                },
+            }
+        }
+
+        // How long until the selection will have settled, for waiting on
+        // input no longer than that
+        fn remaining(&self, now: Instant) -> Option<Duration> {
+            self.moved
+                .map(|moved| SETTLE.saturating_sub(now.duration_since(moved)))
+        }
+    }
+
+    // Fetches entries on a background thread, so moving up or down the list
+    // usually doesn't have to wait for OMDb
+    struct Prefetcher {
+        // Entries the user has moved away from are taken out of this before
+        // they're fetched, and then skipped
+        wanted: Arc<Mutex<Vec<String>>>,
+        fetched: Arc<Mutex<HashMap<String, Entry>>>,
+        // The entry being fetched right now, if any, which is waited on
+        // instead of being asked for twice
+        fetching: Arc<(Mutex<Option<String>>, Condvar)>,
+        queue: mpsc::Sender<String>,
+    }
+
+    impl Prefetcher {
+        fn new<F>(fetch: F) -> Self
+        where
+            F: Fn(&str) -> Result<Entry, RequestError> + Send + 'static,
+        {
+            let wanted = Arc::new(Mutex::new(Vec::<String>::new()));
+            let fetched = Arc::new(Mutex::new(HashMap::new()));
+            let (queue, jobs) = mpsc::channel::<String>();
+            let fetching = Arc::new((Mutex::new(None), Condvar::new()));
+            let still_wanted = Arc::clone(&wanted);
+            let cache = Arc::clone(&fetched);
+            let in_flight = Arc::clone(&fetching);
+            // Stops once the Prefetcher is dropped
+            thread::spawn(move || {
+                let (current, done) = &*in_flight;
+                for imdb_id in jobs {
+                    {
+                        // Checked while holding this, so wait_for can't miss
+                        // an entry that's about to be fetched
+                        let mut current = current.lock().unwrap();
+                        if !still_wanted.lock().unwrap().contains(&imdb_id)
+                            || cache.lock().unwrap().contains_key(&imdb_id)
+                        {
+                            continue;
+                        }
+                        *current = Some(imdb_id.clone());
+                    }
+                    // Errors are left for when the entry is selected, as it's
+                    // fetched again then
+                    if let Ok(entry) = fetch(&imdb_id) {
+                        cache.lock().unwrap().insert(imdb_id, entry);
+                    }
+                    *current.lock().unwrap() = None;
+                    done.notify_all();
+                }
+            });
+            Prefetcher {
+                wanted,
+                fetched,
+                fetching,
+                queue,
+            }
+        }
+
+        // Asks for these entries, instead of any asked for before
+        fn want(&self, imdb_ids: Vec<String>) {
+            let mut wanted = self.wanted.lock().unwrap();
+            if *wanted == imdb_ids {
+                return;
+            }
+            let fetched = self.fetched.lock().unwrap();
+            for imdb_id in &imdb_ids {
+                if !wanted.contains(imdb_id) && !fetched.contains_key(imdb_id) {
+                    // The thread only stops once this is dropped
+                    let _ = self.queue.send(imdb_id.clone());
+                }
+            }
+            *wanted = imdb_ids;
+        }
+
+        fn take(&self, imdb_id: &str) -> Option<Entry> {
+            self.fetched.lock().unwrap().remove(imdb_id)
+        }
+
+        // Waits for the entry if it's being fetched right now, which takes no
+        // longer than the request timeout. Otherwise it's no longer wanted, as
+        // the caller is about to fetch it itself
+        fn wait_for(&self, imdb_id: &str) -> Option<Entry> {
+            let (current, done) = &*self.fetching;
+            let current = current.lock().unwrap();
+            let _current = done
+                .wait_while(current, |current| {
+                    current.as_deref() == Some(imdb_id)
+                })
+                .unwrap();
+            self.wanted
+                .lock()
+                .unwrap()
+                .retain(|wanted| wanted != imdb_id);
+            self.take(imdb_id)
+        }
+    }
+
+    // Where more results come from when the user asks for them. Results that
+    // have already been fetched but not shown are used up before asking OMDb
+    // for the next page
+    struct MoreResults<'b> {
+        bundle: &'b RequestBundle<'b>,
+        backlog: std::vec::IntoIter<SearchResult>,
+        next_page: u32,
+        chunk: usize,
+    }
+
+    impl MoreResults<'_> {
+        fn fetch(&mut self) -> Result<Vec<SearchResult>, RequestError> {
+            if self.backlog.len() == 0 {
+                self.backlog =
+                    self.bundle.get_page(self.next_page, false)?.into_iter();
+                self.next_page += 1;
+            }
+            Ok(self.backlog.by_ref().take(self.chunk).collect())
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    enum LoadState {
+        Disabled,
+        Idle,
+        Loading,
+        Exhausted,
+        Failed,
+    }
+
+    impl LoadState {
+        fn title(self) -> &'static str {
+            use LoadState::*;
+            match self {
+                Disabled => "[Search results] (o: open, f: favourite)",
+                Idle => {
+                    "[Search results] (m: load more, o: open, f: favourite)"
+                },
+                Loading => "[Search results] Loading more…",
+                Exhausted => "[Search results] (no more results)",
+                Failed => "[Search results] (couldn't load more, m: retry)",
+            }
+        }
+    }
+
+    fn load_more(list: &mut StatefulList, more: &mut MoreResults) -> LoadState {
+        loop {
+            match more.fetch() {
+                Ok(results) if results.is_empty() => {
+                    return LoadState::Exhausted
+                },
+                Ok(results) => {
+                    // Try again if everything was already in the list
+                    if list.append(results) > 0 {
+                        return LoadState::Idle;
+                    }
+                },
+                Err(RequestError::Omdb(OmdbError::NotFound)) => {
+                    return LoadState::Exhausted
+                },
+                Err(_) => return LoadState::Failed,
+            }
+        }
+    }
+
+    // Which pane the arrow keys act on, swapped with Tab
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    enum Focus {
+        List,
+        Information,
+    }
+
+    // The input line opened with /, for searching again without restarting
+    #[derive(Default)]
+    struct SearchBar {
+        input: String,
+        searching: bool,
+        // Why the last search didn't replace the list
+        problem: Option<String>,
     }
 
-    pub enum TuiOutcome<'a> {
-        Picked(&'a SearchResult),
-        PickedError(&'a SearchResult, RequestError),
+    impl SearchBar {
+        fn title(&self) -> String {
+            match (&self.problem, self.searching) {
+                (_, true) => "[Search] Searching…".into(),
+                (Some(problem), false) => format!("[Search] {problem}"),
+                (None, false) => "[Search] (Enter: search, Esc: cancel)".into(),
+            }
+        }
+    }
+
+    // Browsing the episodes of a series a season at a time, opened with s
+    struct SeasonBrowser {
+        series: String,
+        imdb_id: String,
+        season: Option<Season>,
+        // The season to fetch once the loading message has been drawn
+        loading: Option<u16>,
+        problem: Option<String>,
+        state: ListState,
+    }
+
+    impl SeasonBrowser {
+        fn new(series: &SearchResult) -> Self {
+            SeasonBrowser {
+                series: series.title.clone(),
+                imdb_id: series.imdb_id.clone(),
+                season: None,
+                loading: Some(1),
+                problem: None,
+                state: ListState::default(),
+            }
+        }
+
+        fn title(&self) -> String {
+            match (&self.season, self.loading, &self.problem) {
+                (_, Some(number), _) => {
+                    format!("[{}] Loading season {number}…", self.series)
+                },
+                (_, None, Some(problem)) => {
+                    format!("[{}] {problem} (Esc: back)", self.series)
+                },
+                (Some(season), None, None) => format!(
+                    "[{}: season {} of {}] (←/→: season, Esc: back)",
+                    self.series, season.season, season.total_seasons,
+                ),
+                (None, None, None) => format!("[{}]", self.series),
+            }
+        }
+
+        // The season before or after the current one, if there is one
+        fn neighbour(&self, forwards: bool) -> Option<u16> {
+            let season = self.season.as_ref()?;
+            if forwards {
+                (season.season < season.total_seasons)
+                    .then_some(season.season + 1)
+            } else {
+                (season.season > 1).then(|| season.season - 1)
+            }
+        }
+
+        fn load(&mut self, api_key: &str) {
+            let Some(number) = self.loading.take() else {
+                return;
+            };
+            match get_season(api_key, &self.imdb_id, number) {
+                Ok(season) => {
+                    let first = (!season.episodes.is_empty()).then_some(0);
+                    self.state.select(first);
+                    self.season = Some(season);
+                    self.problem = None;
+                },
+                Err(why) => {
+                    // Only the first line fits in the title
+                    let why = why.to_string();
+                    let why = why.lines().next().unwrap_or_default();
+                    self.problem =
+                        Some(format!("couldn't load season {number}: {why}"));
+                },
+            }
+        }
+
+        fn episodes(&self) -> &[Episode] {
+            match &self.season {
+                Some(season) => &season.episodes,
+                None => &[],
+            }
+        }
+
+        fn selected(&self) -> Option<&Episode> {
+            self.episodes().get(self.state.selected()?)
+        }
+
+        fn next(&mut self) {
+            let len = self.episodes().len();
+            if let Some(index) = self.state.selected() {
+                self.state.select(Some((index + 1) % len));
+            }
+        }
+
+        fn previous(&mut self) {
+            let len = self.episodes().len();
+            if let Some(index) = self.state.selected() {
+                self.state
+                    .select(Some(index.checked_sub(1).unwrap_or(len - 1)));
+            }
+        }
+
+        fn draw(&mut self, f: &mut Frame, list_area: Rect, area: Rect) {
+            let items = self
+                .episodes()
+                .iter()
+                .map(|episode| {
+                    ListItem::new(format!(
+                        "{}. {}",
+                        episode.episode, episode.title
+                    ))
+                })
+                .collect::<Vec<_>>();
+            let list = List::new(items)
+                .block(
+                    Block::default().title(self.title()).borders(Borders::ALL),
+                )
+                .highlight_symbol(HIGHLIGHT_SYMBOL);
+            f.render_stateful_widget(list, list_area, &mut self.state);
+
+            if let (Some(season), Some(episode)) =
+                (&self.season, self.selected())
+            {
+                let paragraph =
+                    episode_to_paragraph(&self.series, season.season, episode);
+                f.render_widget(paragraph, area);
+            }
+        }
+    }
+
+    pub enum TuiOutcome {
+        Picked(SearchResult),
+        PickedError(SearchResult, RequestError),
+        PickedEpisode(Episode),
         Quit,
     }
 
-    pub fn tui<'a>(
+    // Shows the first `shown` entries of the search. With `paginate`, the
+    // user can load more, starting with any remaining entries before moving
+    // on to page 2. Searching again (with /) uses the same filters and limit
+    // on requests, and the episodes of a series can be picked from instead
+    // (with s)
+    pub fn tui(
         api_key: &str,
-        entries: &'a [SearchResult],
-    ) -> Result<TuiOutcome<'a>, InteractivityError> {
-        let mut status_list = StatefulList::new(entries);
+        mut entries: Vec<SearchResult>,
+        shown: usize,
+        search: &RequestBundle,
+        paginate: bool,
+        full_plot: bool,
+        config: &TuiConfig,
+    ) -> Result<TuiOutcome, InteractivityError> {
+        let backlog = entries.split_off(shown.min(entries.len()));
+        let mut more = paginate.then_some(search).map(|bundle| MoreResults {
+            bundle,
+            backlog: backlog.into_iter(),
+            next_page: bundle.next_page(),
+            chunk: shown.max(1),
+        });
+        let mut load_state = match more {
+            Some(_) => LoadState::Idle,
+            None => LoadState::Disabled,
+        };
+        let mut status_list = StatefulList::new(entries, config);
+        let prefetcher = {
+            let api_key = api_key.to_owned();
+            Prefetcher::new(move |imdb_id| {
+                get_entry_plot(&api_key, imdb_id, full_plot)
+            })
+        };
+        let mut debounce = Debounce::new(status_list.current());
         let mut current_entry_error = None;
+        let mut search_bar = None::<SearchBar>;
+        let mut season_browser = None::<SeasonBrowser>;
+        let mut picked_episode = None::<Episode>;
+        let mut focus = Focus::List;
+        // Shown instead of the entry until the next key press
+        let mut notice = None::<Paragraph<'static>>;
+        // Rows that fit in each pane, for paging through them
+        let mut list_height = 0;
+        let mut information_height = 0;
+        // Where each pane was drawn, for working out what was clicked on
+        let mut list_area = Rect::default();
+        let mut information_area = Rect::default();
+        let mut last_click = None::<(usize, Instant)>;
 
         let mut stdout = io::stdout();
 
         // Crossterm setup
+        let _panic_restore = PanicRestore::install(|| {
+            let _ = Crossterm.restore();
+        });
         enable_raw_mode().map_err(InteractivityError::Crossterm)?;
-        execute!(stdout, EnterAlternateScreen)
+        let guard = TerminalGuard::new(Crossterm);
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
             .map_err(InteractivityError::Crossterm)?;
         let backend = CrosstermBackend::new(stdout);
 
@@ -287,9 +995,23 @@ pub mod tui {
         loop {
             terminal
                 .draw(|f| {
+                    let search_height = match search_bar {
+                        Some(_) => 3,
+                        None => 0,
+                    };
+                    let rows = Layout::default()
+                        .direction(Direction::Vertical)
+                        .margin(1)
+                        .constraints(
+                            [
+                                Constraint::Min(0),
+                                Constraint::Length(search_height),
+                            ]
+                            .as_slice(),
+                        )
+                        .split(f.size());
                     let chunks = Layout::default()
                         .direction(Direction::Horizontal)
-                        .margin(1)
                         .constraints(
                             [
                                 Constraint::Percentage(40),
@@ -297,8 +1019,16 @@ pub mod tui {
                             ]
                             .as_slice(),
                         )
-                        .split(f.size());
+                        .split(rows[0]);
+
+                    if let Some(browser) = season_browser.as_mut() {
+                        browser.draw(f, chunks[0], chunks[1]);
+                        return;
+                    }
 
+                    list_height = chunks[0].height.saturating_sub(2) as usize;
+                    list_area = chunks[0];
+                    information_area = chunks[1];
                     // subtract width of borders
                     let width = chunks[0].width.saturating_sub(2) as usize;
                     let width = width.saturating_sub(HIGHLIGHT_SYMBOL.len());
@@ -308,7 +1038,7 @@ pub mod tui {
                     let selection_list = List::new(items)
                         .block(
                             Block::default()
-                                .title("[Search results]")
+                                .title(load_state.title())
                                 .borders(Borders::ALL),
                         )
                         .highlight_symbol(HIGHLIGHT_SYMBOL);
@@ -319,70 +1049,488 @@ pub mod tui {
                         &mut status_list.state,
                     );
 
-                    match status_list.entry(api_key) {
-                        Ok(entry) => {
-                            f.render_widget(entry, chunks[1]);
-                            current_entry_error = None;
-                        },
-                        Err(why) => {
-                            // Fall back on rendering the error as a Paragraph
-                            f.render_widget(
-                                error_to_paragraph(&why),
-                                chunks[1],
-                            );
-                            current_entry_error = Some(why);
-                        },
+                    information_height = chunks[1].height.saturating_sub(2);
+                    let scroll = status_list.scroll;
+                    if let Some(notice) = &notice {
+                        f.render_widget(notice.clone(), chunks[1]);
+                    } else if !debounce
+                        .settled(status_list.current(), Instant::now())
+                    {
+                        let loading = status_list
+                            .cached_entry(&prefetcher)
+                            .unwrap_or_else(|| {
+                                Paragraph::new("").block(
+                                    Block::default()
+                                        .title("[Information] Loading…")
+                                        .borders(Borders::ALL),
+                                )
+                            });
+                        f.render_widget(loading.scroll((scroll, 0)), chunks[1]);
+                        current_entry_error = None;
+                    } else {
+                        let entry =
+                            status_list.entry(api_key, full_plot, &prefetcher);
+                        prefetcher.want(status_list.neighbours());
+                        match entry {
+                            Ok(entry) => {
+                                let entry = match focus {
+                                    Focus::List => entry,
+                                    Focus::Information => entry.block(
+                                        Block::default()
+                                            .title(FOCUSED_INFORMATION)
+                                            .borders(Borders::ALL)
+                                            .border_style(*BOLD),
+                                    ),
+                                };
+                                f.render_widget(
+                                    entry.scroll((scroll, 0)),
+                                    chunks[1],
+                                );
+                                current_entry_error = None;
+                            },
+                            Err(why) => {
+                                // Fall back on rendering the error as a Paragraph
+                                f.render_widget(
+                                    error_to_paragraph(&why),
+                                    chunks[1],
+                                );
+                                current_entry_error = Some(why);
+                            },
+                        }
+                    }
+
+                    if let Some(bar) = &search_bar {
+                        let input = Paragraph::new(bar.input.clone()).block(
+                            Block::default()
+                                .title(bar.title())
+                                .borders(Borders::ALL),
+                        );
+                        f.render_widget(input, rows[1]);
+                        // Put the cursor after what's been typed so far
+                        let typed = bar.input.chars().count() as u16;
+                        f.set_cursor(
+                            (rows[1].x + 1 + typed)
+                                .min(rows[1].right().saturating_sub(2)),
+                            rows[1].y + 1,
+                        );
                     }
                 })
                 .map_err(InteractivityError::Tui)?;
 
-            // Blocks until key press or terminal resize
-            if let Event::Key(key) =
-                event::read().map_err(InteractivityError::Crossterm)?
+            if let Some(browser) =
+                season_browser.as_mut().filter(|b| b.loading.is_some())
             {
+                // The loading message has been drawn, now fetch the season
+                browser.load(api_key);
+                continue;
+            }
+
+            if let Some(bar) = search_bar.as_mut().filter(|bar| bar.searching) {
+                // The searching message has been drawn, now do the search
+                bar.searching = false;
+                let search_term = bar.input.trim();
+                let bundle = search.search_again(search_term);
+                match bundle.get_results(shown.max(1), false) {
+                    Ok(Matches { mut results, .. }) if !results.is_empty() => {
+                        results.truncate(shown.max(1));
+                        status_list.replace(results);
+                        // More results would come from the old search
+                        more = None;
+                        load_state = LoadState::Disabled;
+                        search_bar = None;
+                    },
+                    Ok(_) => {
+                        bar.problem =
+                            Some(format!("no results for {search_term:?}"))
+                    },
+                    Err(why) => bar.problem = Some(why.to_string()),
+                }
+                continue;
+            }
+
+            if load_state == LoadState::Loading {
+                // The loading message has been drawn, now do the fetch
+                if let Some(more) = more.as_mut() {
+                    load_state = load_more(&mut status_list, more);
+                }
+                continue;
+            }
+
+            // Waits for the selection to settle before drawing again to fetch
+            // its entry, unless there's more input first
+            if let Some(remaining) = debounce.remaining(Instant::now()) {
+                if !event::poll(remaining)
+                    .map_err(InteractivityError::Crossterm)?
+                {
+                    continue;
+                }
+            }
+            // Blocks until key press or terminal resize
+            let event = event::read().map_err(InteractivityError::Crossterm)?;
+            if let Event::Mouse(mouse) = event {
+                if search_bar.is_some() || season_browser.is_some() {
+                    continue;
+                }
+                let position = Rect::new(mouse.column, mouse.row, 1, 1);
+                match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        let clicked = item_at(
+                            list_area,
+                            status_list.state.offset(),
+                            &status_list.heights(),
+                            mouse.column,
+                            mouse.row,
+                        );
+                        if let Some(index) = clicked {
+                            let now = Instant::now();
+                            let double_click = matches!(
+                                last_click,
+                                Some((last, at))
+                                    if last == index
+                                        && now - at <= DOUBLE_CLICK
+                            );
+                            if double_click {
+                                break;
+                            }
+                            if index != status_list.current() {
+                                notice = None;
+                                status_list.select(index);
+                            }
+                            last_click = Some((index, now));
+                        }
+                    },
+                    MouseEventKind::ScrollUp
+                        if list_area.intersects(position) =>
+                    {
+                        status_list.previous()
+                    },
+                    MouseEventKind::ScrollDown
+                        if list_area.intersects(position) =>
+                    {
+                        status_list.next()
+                    },
+                    MouseEventKind::ScrollUp
+                        if information_area.intersects(position) =>
+                    {
+                        status_list.scroll_up(1)
+                    },
+                    MouseEventKind::ScrollDown
+                        if information_area.intersects(position) =>
+                    {
+                        status_list.scroll_down(1)
+                    },
+                    _ => {},
+                }
+                continue;
+            }
+            if let Event::Key(key) = event {
+                // Raw mode means Ctrl-C arrives as a key press instead of
+                // killing the process, so it quits from anywhere
+                if is_interrupt(&key) {
+                    drop(terminal);
+                    guard.finish().map_err(InteractivityError::Crossterm)?;
+                    return Ok(TuiOutcome::Quit);
+                }
+                // Typing goes in the search bar while it's open. Leaving it
+                // without searching keeps the list as it was
+                if let Some(bar) = search_bar.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => search_bar = None,
+                        KeyCode::Enter if !bar.input.trim().is_empty() => {
+                            bar.searching = true
+                        },
+                        KeyCode::Backspace => {
+                            bar.input.pop();
+                        },
+                        KeyCode::Char(c) => bar.input.push(c),
+                        _ => {},
+                    }
+                    continue;
+                }
+                if let Some(browser) = season_browser.as_mut() {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') => {
+                            season_browser = None
+                        },
+                        KeyCode::Enter => {
+                            if let Some(episode) = browser.selected() {
+                                picked_episode = Some(episode.clone());
+                                break;
+                            }
+                        },
+                        KeyCode::Up | KeyCode::Char('k') => browser.previous(),
+                        KeyCode::Down | KeyCode::Char('j') => browser.next(),
+                        KeyCode::Left | KeyCode::Char('h') => {
+                            browser.loading = browser.neighbour(false)
+                        },
+                        KeyCode::Right | KeyCode::Char('l') => {
+                            browser.loading = browser.neighbour(true)
+                        },
+                        _ => {},
+                    }
+                    continue;
+                }
+                notice = None;
                 match key.code {
                     KeyCode::Esc | KeyCode::Char('q') => {
-                        unwind(terminal.backend_mut())
+                        drop(terminal);
+                        guard
+                            .finish()
                             .map_err(InteractivityError::Crossterm)?;
                         return Ok(TuiOutcome::Quit);
                     },
                     KeyCode::Enter => break,
+                    KeyCode::Tab => {
+                        focus = match focus {
+                            Focus::List => Focus::Information,
+                            Focus::Information => Focus::List,
+                        }
+                    },
+                    KeyCode::PageUp if focus == Focus::Information => {
+                        status_list.scroll_up(information_height.max(1))
+                    },
+                    KeyCode::PageDown if focus == Focus::Information => {
+                        status_list.scroll_down(information_height.max(1))
+                    },
+                    KeyCode::PageUp => status_list.page_up(list_height),
+                    KeyCode::PageDown => status_list.page_down(list_height),
+                    KeyCode::Home => status_list.first(),
+                    KeyCode::End => status_list.last(),
+                    KeyCode::Up
+                        if focus == Focus::Information
+                            || key.modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        status_list.scroll_up(1)
+                    },
+                    KeyCode::Down
+                        if focus == Focus::Information
+                            || key.modifiers.contains(KeyModifiers::SHIFT) =>
+                    {
+                        status_list.scroll_down(1)
+                    },
+                    KeyCode::Char('k') if focus == Focus::Information => {
+                        status_list.scroll_up(1)
+                    },
+                    KeyCode::Char('j') if focus == Focus::Information => {
+                        status_list.scroll_down(1)
+                    },
                     KeyCode::Up | KeyCode::Char('k') => status_list.previous(),
                     KeyCode::Down | KeyCode::Char('j') => status_list.next(),
+                    KeyCode::Char('m')
+                        if matches!(
+                            load_state,
+                            LoadState::Idle | LoadState::Failed
+                        ) =>
+                    {
+                        load_state = LoadState::Loading
+                    },
+                    KeyCode::Char('/') => {
+                        search_bar = Some(SearchBar::default())
+                    },
+                    KeyCode::Char('s') => {
+                        let chosen =
+                            &status_list.underlying[status_list.current()];
+                        if chosen.media_type.is_series() {
+                            season_browser = Some(SeasonBrowser::new(chosen));
+                        }
+                    },
+                    KeyCode::Char('f') => {
+                        let chosen =
+                            &status_list.underlying[status_list.current()];
+                        let added = Favourites::add(chosen);
+                        notice = Some(favourite_to_paragraph(chosen, added));
+                    },
+                    KeyCode::Char('o') => {
+                        // The ID is known even if the entry failed to load
+                        let imdb_id = &status_list.underlying
+                            [status_list.current()]
+                        .imdb_id;
+                        let url = format!("{WEB_URL}{imdb_id}/");
+                        if let Err(why) = opener::open_browser(&url) {
+                            notice = Some(open_error_to_paragraph(&url, &why));
+                        }
+                    },
                     _ => {},
                 }
             }
         }
 
         // Crossterm unwind
-        unwind(terminal.backend_mut())
-            .map_err(InteractivityError::Crossterm)?;
-        let chosen = &entries[status_list.current()];
+        drop(terminal);
+        guard.finish().map_err(InteractivityError::Crossterm)?;
+        if let Some(episode) = picked_episode {
+            return Ok(TuiOutcome::PickedEpisode(episode));
+        }
+        let index = status_list.current();
+        let chosen = status_list.underlying.swap_remove(index);
         match current_entry_error {
             None => Ok(TuiOutcome::Picked(chosen)),
             Some(err) => Ok(TuiOutcome::PickedError(chosen, err)),
         }
     }
 
+    fn is_interrupt(key: &KeyEvent) -> bool {
+        key.code == KeyCode::Char('c')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    // Undoes the crossterm setup
+    trait Restore {
+        fn restore(&mut self) -> io::Result<()>;
+    }
+
+    struct Crossterm;
+
+    impl Restore for Crossterm {
+        fn restore(&mut self) -> io::Result<()> {
+            unwind(&mut io::stdout())
+        }
+    }
+
+    // Restores the terminal when dropped, so it isn't left in raw mode if the
+    // TUI returns early with an error or panics
+    struct TerminalGuard<R: Restore> {
+        restore: Option<R>,
+    }
+
+    impl<R: Restore> TerminalGuard<R> {
+        fn new(restore: R) -> Self {
+            TerminalGuard {
+                restore: Some(restore),
+            }
+        }
+
+        // Restores the terminal now, so any error can be reported
+        fn finish(mut self) -> io::Result<()> {
+            match self.restore.take() {
+                Some(mut restore) => restore.restore(),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl<R: Restore> Drop for TerminalGuard<R> {
+        fn drop(&mut self) {
+            if let Some(mut restore) = self.restore.take() {
+                // Nothing more can be done if this fails
+                let _ = restore.restore();
+            }
+        }
+    }
+
+    // A panic's message is printed before anything is dropped, so the guard
+    // alone would restore the terminal only after the message had gone onto
+    // the alternate screen, to be wiped with it. This restores it first, and
+    // puts the previous panic hook back when dropped
+    struct PanicRestore {
+        put_back: Option<Box<dyn FnOnce()>>,
+    }
+
+    impl PanicRestore {
+        fn install(restore: impl Fn() + Send + Sync + 'static) -> Self {
+            let previous = Arc::new(panic::take_hook());
+            let chained = Arc::clone(&previous);
+            panic::set_hook(Box::new(move |info| {
+                restore();
+                chained(info);
+            }));
+            let put_back = move || {
+                // Dropping this hook lets go of its handle on the previous one
+                drop(panic::take_hook());
+                if let Ok(previous) = Arc::try_unwrap(previous) {
+                    panic::set_hook(previous);
+                }
+            };
+            PanicRestore {
+                put_back: Some(Box::new(put_back)),
+            }
+        }
+    }
+
+    impl Drop for PanicRestore {
+        fn drop(&mut self) {
+            // The hook can't be changed while panicking, but it doesn't
+            // matter as nothing is left to restore by then
+            if thread::panicking() {
+                return;
+            }
+            if let Some(put_back) = self.put_back.take() {
+                put_back();
+            }
+        }
+    }
+
     // Crossterm unwind
-    fn unwind(stdout: &mut CrosstermBackend<Stdout>) -> io::Result<()> {
+    fn unwind(stdout: &mut impl io::Write) -> io::Result<()> {
+        // Mouse capture goes first so it's off even if the rest fails
+        let mouse = execute!(stdout, DisableMouseCapture);
         disable_raw_mode()?;
-        execute!(stdout, LeaveAlternateScreen)
+        execute!(stdout, LeaveAlternateScreen)?;
+        mouse
+    }
+
+    // Which item of a list drawn in area (with a border) is at the given
+    // position. Each row of an item counts as it, including the highlight
+    // symbol, and offset is how many items have been scrolled past
+    fn item_at(
+        area: Rect,
+        offset: usize,
+        heights: &[usize],
+        column: u16,
+        row: u16,
+    ) -> Option<usize> {
+        let inner = area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        if !inner.intersects(Rect::new(column, row, 1, 1)) {
+            return None;
+        }
+        let mut rows = usize::from(row - inner.y);
+        for (index, &height) in heights.iter().enumerate().skip(offset) {
+            if rows < height {
+                return Some(index);
+            }
+            rows -= height;
+        }
+        None
+    }
+
+    fn entry_to_paragraph(
+        entry: Entry,
+        config: &TuiConfig,
+    ) -> Paragraph<'static> {
+        Paragraph::new(entry_lines(entry, config))
+            .block(
+                Block::default()
+                    .title("[Information]")
+                    .borders(Borders::ALL),
+            )
+            .wrap(Wrap { trim: false })
     }
 
-    fn entry_to_paragraph(entry: Entry) -> Paragraph<'static> {
+    fn entry_lines(entry: Entry, config: &TuiConfig) -> Vec<Line<'static>> {
         let Entry {
             title,
             year,
             rating,
+            votes,
+            metascore,
+            ratings,
+            box_office,
+            awards,
             runtime,
+            released,
             genres,
+            language,
+            directors,
+            writers,
             actors,
             plot,
             seasons,
             ..
         } = entry;
-        let mut information = Vec::with_capacity(6);
+        let mut information = Vec::with_capacity(12);
         // Line 1: title & year
         information.push(Line::from(vec![
             Span::styled("Title: ", *BOLD),
@@ -400,7 +1548,7 @@ pub mod tui {
                     Span::styled("Seasons: ", *BOLD),
                     Span::raw(seasons.to_string()),
                     Span::raw(" ("),
-                    Span::raw(runtime),
+                    Span::raw(runtime.to_string()),
                     Span::raw(" per episode)"),
                 ]));
             },
@@ -415,46 +1563,155 @@ pub mod tui {
                 // e.g. Run time: 120 minutes
                 information.push(Line::from(vec![
                     Span::styled("Run time: ", *BOLD),
-                    Span::raw(runtime),
+                    Span::raw(runtime.to_string()),
                 ]));
             },
             (None, None) => {},
         }
-        // Line 3: rating
+        // Line 3: release date
+        if let Some(released) = released {
+            information.push(Line::from(vec![
+                Span::styled("Released: ", *BOLD),
+                Span::raw(format_date(released)),
+            ]));
+        }
+        // Line 4: rating & metascore
+        let mut scores = Vec::with_capacity(5);
         if let Some(rating) = rating {
+            let mut rating = if config.star_rating {
+                format!("{} {rating}", rating_to_stars(rating))
+            } else {
+                rating.to_string()
+            };
+            if let Some(votes) = votes {
+                // e.g. 8.2 (966,025 votes)
+                let _ = write!(rating, " ({} votes)", thousands(votes.into()));
+            }
+            scores.push(Span::styled("IMDb Rating: ", *BOLD));
+            scores.push(Span::raw(rating));
+        }
+        if let Some(metascore) = metascore {
+            // e.g. IMDb Rating: 8.2 (966,025 votes), Metascore: 88
+            if !scores.is_empty() {
+                scores.push(Span::raw(", "));
+            }
+            scores.push(Span::styled("Metascore: ", *BOLD));
+            scores.push(Span::raw(metascore.to_string()));
+        }
+        if !scores.is_empty() {
+            information.push(Line::from(scores));
+        }
+        // Other sources' ratings, leaving out those already shown
+        for rating in ratings {
+            let shown = match rating.source {
+                RatingSource::Imdb => true,
+                RatingSource::Metacritic => metascore.is_some(),
+                _ => false,
+            };
+            if !shown {
+                information.push(Line::from(vec![
+                    Span::styled(format!("{} Rating: ", rating.source), *BOLD),
+                    Span::raw(rating.value),
+                ]));
+            }
+        }
+        // Line 5: box office
+        if let Some(box_office) = box_office {
             information.push(Line::from(vec![
-                Span::styled("IMDb Rating: ", *BOLD),
-                Span::raw(rating.to_string()),
+                Span::styled("Box Office: ", *BOLD),
+                Span::raw(format!("${}", thousands(box_office))),
+            ]));
+        }
+        // Line 6: awards
+        if let Some(awards) = awards {
+            information.push(Line::from(vec![
+                Span::styled("Awards: ", *BOLD),
+                Span::raw(format_awards(awards)),
             ]));
         }
-        // Line 4: genres
+        // Line 7: genres
         if let Some(genres) = genres {
             information.push(Line::from(vec![
                 Span::styled("Genre(s): ", *BOLD),
                 Span::raw(format_list(&genres)),
             ]));
         }
-        // Line 5: actors
+        // Line 8: languages
+        if let Some(languages) = language {
+            information.push(Line::from(vec![
+                Span::styled("Language(s): ", *BOLD),
+                Span::raw(format_list(&languages)),
+            ]));
+        }
+        // Line 9: directors
+        if let Some(directors) = directors {
+            information.push(Line::from(vec![
+                Span::styled("Director(s): ", *BOLD),
+                Span::raw(format_list(&directors)),
+            ]));
+        }
+        // Line 10: writers
+        if let Some(writers) = writers {
+            information.push(Line::from(vec![
+                Span::styled("Writer(s): ", *BOLD),
+                Span::raw(format_list(&writers)),
+            ]));
+        }
+        // Line 11: actors
         if let Some(actors) = actors {
             information.push(Line::from(vec![
                 Span::styled("Actor(s): ", *BOLD),
                 Span::raw(format_list(&actors)),
             ]));
         }
-        // Line 6: plot
+        // Line 12: plot
         if let Some(plot) = plot {
             information.push(Line::from(vec![
                 Span::styled("Plot: ", *BOLD),
                 Span::raw(plot),
             ]));
         }
+        information
+    }
+
+    fn episode_to_paragraph(
+        series: &str,
+        season: u16,
+        episode: &Episode,
+    ) -> Paragraph<'static> {
+        let mut information = vec![
+            Line::from(vec![
+                Span::styled("Title: ", *BOLD),
+                Span::raw(episode.title.clone()),
+            ]),
+            // e.g. Episode: Breakout Kings S01E03
+            Line::from(vec![
+                Span::styled("Episode: ", *BOLD),
+                Span::raw(format!(
+                    "{series} S{season:02}E{:02}",
+                    episode.episode
+                )),
+            ]),
+        ];
+        if let Some(released) = &episode.released {
+            information.push(Line::from(vec![
+                Span::styled("Released: ", *BOLD),
+                Span::raw(released.clone()),
+            ]));
+        }
+        if let Some(rating) = episode.rating {
+            information.push(Line::from(vec![
+                Span::styled("IMDb Rating: ", *BOLD),
+                Span::raw(rating.to_string()),
+            ]));
+        }
+        information.push(Line::from(vec![
+            Span::styled("IMDb ID: ", *BOLD),
+            Span::raw(episode.imdb_id.clone()),
+        ]));
 
         Paragraph::new(information)
-            .block(
-                Block::default()
-                    .title("[Information]")
-                    .borders(Borders::ALL),
-            )
+            .block(Block::default().title("[Episode]").borders(Borders::ALL))
             .wrap(Wrap { trim: false })
     }
 
@@ -475,6 +1732,109 @@ pub mod tui {
             .wrap(Wrap { trim: false })
     }
 
+    fn open_error_to_paragraph(
+        url: &str,
+        error: &opener::OpenError,
+    ) -> Paragraph<'static> {
+        let mut text = vec![
+            Line::from(Span::styled("Failed to open your browser", *BOLD)),
+            Line::from(Span::styled(format!("Please visit {url}"), *BOLD)),
+        ];
+
+        for line in error.to_string().lines() {
+            text.push(Line::from(line.to_owned()));
+        }
+
+        Paragraph::new(text)
+            .block(Block::default().title("[Uh oh]").borders(Borders::ALL))
+            .wrap(Wrap { trim: false })
+    }
+
+    fn favourite_to_paragraph(
+        search_result: &SearchResult,
+        added: Result<bool, DiskError>,
+    ) -> Paragraph<'static> {
+        let paragraph = match added {
+            Ok(added) => {
+                let text = match added {
+                    true => format!("Added {search_result} to your favourites"),
+                    false => {
+                        format!(
+                            "{search_result} is already one of your favourites"
+                        )
+                    },
+                };
+                Paragraph::new(Line::from(text)).block(
+                    Block::default()
+                        .title("[Favourites]")
+                        .borders(Borders::ALL),
+                )
+            },
+            Err(why) => {
+                let mut text = vec![Line::from(Span::styled(
+                    "Failed to add to your favourites",
+                    *BOLD,
+                ))];
+                for line in why.to_string().lines() {
+                    text.push(Line::from(line.to_owned()));
+                }
+                Paragraph::new(text).block(
+                    Block::default().title("[Uh oh]").borders(Borders::ALL),
+                )
+            },
+        };
+        paragraph.wrap(Wrap { trim: false })
+    }
+
+    // 966025 => "966,025"
+    fn thousands(n: u64) -> String {
+        let digits = n.to_string();
+        let mut buf = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index != 0 && (digits.len() - index) % 3 == 0 {
+                buf.push(',');
+            }
+            buf.push(digit);
+        }
+        buf
+    }
+
+    // e.g. "2 Oscars, 79 wins, and 87 nominations", leaving out any that are 0
+    fn format_awards(awards: Awards) -> String {
+        let counted = [
+            (awards.oscars_won, "Oscar"),
+            (awards.wins, "win"),
+            (awards.nominations, "nomination"),
+        ]
+        .into_iter()
+        .filter(|&(count, _)| count > 0)
+        .map(|(count, noun)| match count {
+            1 => format!("1 {noun}"),
+            count => format!("{count} {noun}s"),
+        })
+        .collect::<Vec<_>>();
+        format_list(&counted)
+    }
+
+    // e.g. "29 May 2009", with the month in full
+    fn format_date(date: Date) -> String {
+        date.format(format_description!(
+            "[day padding:none] [month repr:long] [year]"
+        ))
+        .unwrap_or_else(|_| date.to_string())
+    }
+
+    // Maps a rating out of 10 to a row of STARS stars, rounding to the
+    // nearest star
+    fn rating_to_stars(rating: f32) -> String {
+        let filled =
+            (rating.clamp(0.0, 10.0) / 10.0 * STARS as f32).round() as usize;
+        let mut buf = String::with_capacity(STARS * '★'.len_utf8());
+        (0..STARS)
+            .for_each(|i| buf.push(if i < filled { '★' } else { '☆' }));
+        buf
+    }
+
     fn format_list<S: Display>(strings: &[S]) -> String {
         match strings.len() {
             0 => String::new(),
@@ -493,7 +1853,24 @@ pub mod tui {
 
     #[cfg(test)]
     mod unit_tests {
-        use super::format_list;
+        use super::{
+            entry_lines, format_list, is_interrupt, item_at, rating_to_stars,
+            thousands, Debounce, ListFormat, PanicRestore, Prefetcher, Restore,
+            SeasonBrowser, StatefulList, TerminalGuard, SETTLE,
+        };
+        use crate::omdb::SearchResult;
+        use crate::omdb::{Entry, Episode, MediaType, Season};
+        use crate::{ListFormatParseError, TuiConfig, Year};
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Paragraph;
+        use std::cell::Cell;
+        use std::io;
+        use std::panic::AssertUnwindSafe;
+        use std::rc::Rc;
+        use std::sync::{mpsc, Arc, Mutex};
+        use std::thread;
+        use std::time::{Duration, Instant};
 
         #[test]
         fn correct_lists() {
@@ -523,5 +1900,446 @@ pub mod tui {
                 assert!(output.contains(item), "missing {item} in list");
             }
         }
+
+        #[test]
+        fn thousands_separators() {
+            assert_eq!(thousands(0), "0");
+            assert_eq!(thousands(999), "999");
+            assert_eq!(thousands(15_196), "15,196");
+            assert_eq!(thousands(966_025), "966,025");
+            assert_eq!(thousands(1_234_567), "1,234,567");
+        }
+
+        #[test]
+        fn stars() {
+            assert_eq!(rating_to_stars(0.0), "☆☆☆☆☆");
+            assert_eq!(rating_to_stars(10.0), "★★★★★");
+            assert_eq!(rating_to_stars(8.2), "★★★★☆");
+            assert_eq!(rating_to_stars(7.3), "★★★★☆");
+            assert_eq!(rating_to_stars(4.9), "★★☆☆☆");
+            // Out of range values are clamped
+            assert_eq!(rating_to_stars(11.0), "★★★★★");
+            assert_eq!(rating_to_stars(-1.0), "☆☆☆☆☆");
+        }
+
+        #[test]
+        fn directors_and_writers() {
+            let up = r#"{"Title":"Up","Year":"2009","Runtime":"96 min","Genre":"Animation, Adventure, Comedy","Director":"Pete Docter, Bob Peterson","Writer":"Pete Docter, Bob Peterson, Tom McCarthy","Actors":"Edward Asner, Jordan Nagai, John Ratzenberger","Plot":"78-year-old Carl Fredricksen travels to Paradise Falls in his house equipped with balloons, inadvertently taking a young stowaway.","Language":"English","Country":"United States","imdbRating":"8.2","imdbVotes":"966,025","imdbID":"tt1049413","Type":"movie","Released":"29 May 2009","BoxOffice":"$293,004,164","Metascore":"88","Awards":"Won 2 Oscars. 79 wins & 87 nominations total","Response":"True"}"#;
+            let entry = serde_json::from_str::<Entry>(up).unwrap();
+            let lines = entry_lines(entry, &TuiConfig::default())
+                .into_iter()
+                .map(|line| {
+                    line.spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            let position = |prefix: &str| {
+                lines.iter().position(|line| line.starts_with(prefix))
+            };
+
+            assert!(lines.contains(&String::from(
+                "Director(s): Pete Docter and Bob Peterson"
+            )));
+            assert!(lines.contains(&String::from(
+                "Writer(s): Pete Docter, Bob Peterson, and Tom McCarthy"
+            )));
+            assert!(lines.contains(&String::from("Language(s): English")));
+            assert!(lines.contains(&String::from("Released: 29 May 2009")));
+            assert!(lines.contains(&String::from(
+                "Awards: 2 Oscars, 79 wins, and 87 nominations"
+            )));
+            assert!(lines.contains(&String::from(
+                "IMDb Rating: 8.2 (966,025 votes), Metascore: 88"
+            )));
+            assert!(lines.contains(&String::from("Box Office: $293,004,164")));
+            // Between the genres and the actors
+            let order = [
+                "Genre(s): ",
+                "Language(s): ",
+                "Director(s): ",
+                "Writer(s): ",
+                "Actor(s): ",
+            ]
+            .map(|prefix| position(prefix).unwrap());
+            assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        #[test]
+        fn list_format() {
+            let sr = SearchResult {
+                title: "Black Mirror".into(),
+                imdb_id: "tt2085059".into(),
+                media_type: MediaType::SERIES,
+                year: Year(2011..=2019),
+                poster: None,
+            };
+            let format = "{year} — {title} [{type}]".parse::<ListFormat>();
+            assert_eq!(
+                format.unwrap().apply(&sr),
+                "2011-2019 — Black Mirror [series]"
+            );
+            let format = "{title} ({id})".parse::<ListFormat>();
+            assert_eq!(format.unwrap().apply(&sr), "Black Mirror (tt2085059)");
+            let format = "{{{title}}}".parse::<ListFormat>();
+            assert_eq!(format.unwrap().apply(&sr), "{Black Mirror}");
+        }
+
+        #[test]
+        fn list_format_invalid() {
+            assert_eq!(
+                "{title} {rating}".parse::<ListFormat>().unwrap_err(),
+                ListFormatParseError::UnknownField("rating".into()),
+            );
+            assert_eq!(
+                "{title".parse::<ListFormat>().unwrap_err(),
+                ListFormatParseError::Unclosed,
+            );
+        }
+
+        #[test]
+        fn append_to_list() {
+            let result = |id: &str| SearchResult {
+                title: id.into(),
+                imdb_id: id.into(),
+                media_type: MediaType::MOVIE,
+                year: Year(2000..=2000),
+                poster: None,
+            };
+            let config = TuiConfig::default();
+            let mut list =
+                StatefulList::new(vec![result("tt1"), result("tt2")], &config);
+            list.next();
+            assert_eq!(list.current(), 1);
+
+            // Duplicates are skipped, and the first new result is selected
+            let added = list.append([result("tt2"), result("tt3")]);
+            assert_eq!(added, 1);
+            assert_eq!(list.underlying.len(), 3);
+            assert_eq!(list.entry_paragraphs.len(), 3);
+            assert_eq!(list.current(), 2);
+
+            // Nothing new leaves the selection alone
+            assert_eq!(list.append([result("tt1")]), 0);
+            assert_eq!(list.current(), 2);
+
+            // Moving to another result goes back to the top of its details
+            list.scroll_down(5);
+            list.scroll_up(2);
+            assert_eq!(list.scroll, 3);
+            list.previous();
+            assert_eq!(list.scroll, 0);
+            list.scroll_up(1);
+            assert_eq!(list.scroll, 0);
+
+            // Searching again starts from scratch
+            list.replace(vec![result("tt4")]);
+            assert_eq!(list.underlying.len(), 1);
+            assert_eq!(list.entry_paragraphs.len(), 1);
+            assert_eq!(list.current(), 0);
+        }
+
+        #[test]
+        fn paging() {
+            let results = (0..10)
+                .map(|n| SearchResult {
+                    title: n.to_string(),
+                    imdb_id: format!("tt{n}"),
+                    media_type: MediaType::MOVIE,
+                    year: Year(2000..=2000),
+                    poster: None,
+                })
+                .collect();
+            let config = TuiConfig::default();
+            let mut list = StatefulList::new(results, &config);
+
+            list.page_down(4);
+            assert_eq!(list.current(), 4);
+            list.page_down(4);
+            assert_eq!(list.current(), 8);
+            // Stops at the last item, then wraps around like next
+            list.page_down(4);
+            assert_eq!(list.current(), 9);
+            list.page_down(4);
+            assert_eq!(list.current(), 0);
+
+            // And the same going up
+            list.page_up(4);
+            assert_eq!(list.current(), 9);
+            list.page_up(4);
+            assert_eq!(list.current(), 5);
+            list.page_up(4);
+            assert_eq!(list.current(), 1);
+            list.page_up(4);
+            assert_eq!(list.current(), 0);
+
+            // A pane too small to show anything still moves
+            list.page_down(0);
+            assert_eq!(list.current(), 1);
+
+            list.last();
+            assert_eq!(list.current(), 9);
+            list.first();
+            assert_eq!(list.current(), 0);
+        }
+
+        #[test]
+        fn season_browser() {
+            let series = SearchResult {
+                title: "Breakout Kings".into(),
+                imdb_id: "tt1590961".into(),
+                media_type: MediaType::SERIES,
+                year: Year(2011..=2012),
+                poster: None,
+            };
+            let episode = |episode: u16| Episode {
+                title: format!("Episode {episode}"),
+                episode,
+                imdb_id: format!("tt{episode:07}"),
+                released: None,
+                rating: None,
+            };
+            let mut browser = SeasonBrowser::new(&series);
+            // Season 1 is loaded first, and there's nowhere to go until then
+            assert_eq!(browser.loading, Some(1));
+            assert_eq!(browser.neighbour(true), None);
+            assert!(browser.selected().is_none());
+
+            browser.loading = None;
+            browser.season = Some(Season {
+                title: series.title.clone(),
+                season: 1,
+                total_seasons: 2,
+                episodes: vec![episode(1), episode(2), episode(3)],
+            });
+            browser.state.select(Some(0));
+            assert_eq!(browser.neighbour(false), None);
+            assert_eq!(browser.neighbour(true), Some(2));
+
+            // Moving wraps around like the results list
+            browser.previous();
+            assert_eq!(browser.selected().unwrap().episode, 3);
+            browser.next();
+            assert_eq!(browser.selected().unwrap().imdb_id, "tt0000001");
+
+            browser.season.as_mut().unwrap().season = 2;
+            assert_eq!(browser.neighbour(false), Some(1));
+            assert_eq!(browser.neighbour(true), None);
+        }
+
+        #[test]
+        fn click_to_index() {
+            // Rows 1 to 4 are inside the border, the second item is wrapped
+            let area = Rect::new(0, 0, 20, 6);
+            let heights = [1, 2, 1, 1];
+            assert_eq!(item_at(area, 0, &heights, 5, 0), None);
+            assert_eq!(item_at(area, 0, &heights, 5, 1), Some(0));
+            assert_eq!(item_at(area, 0, &heights, 5, 2), Some(1));
+            assert_eq!(item_at(area, 0, &heights, 5, 3), Some(1));
+            assert_eq!(item_at(area, 0, &heights, 5, 4), Some(2));
+            assert_eq!(item_at(area, 0, &heights, 5, 5), None);
+            // The highlight symbol is part of the item, the border isn't
+            assert_eq!(item_at(area, 0, &heights, 1, 1), Some(0));
+            assert_eq!(item_at(area, 0, &heights, 0, 1), None);
+            assert_eq!(item_at(area, 0, &heights, 19, 1), None);
+            // Scrolled down the list
+            assert_eq!(item_at(area, 1, &heights, 5, 1), Some(1));
+            assert_eq!(item_at(area, 2, &heights, 5, 2), Some(3));
+            // Below the last item
+            assert_eq!(item_at(area, 0, &[1], 5, 2), None);
+            // Somewhere else entirely
+            let area = Rect::new(10, 10, 20, 6);
+            assert_eq!(item_at(area, 0, &heights, 5, 5), None);
+        }
+
+        #[test]
+        fn prefetching() {
+            let result = |id: &str| SearchResult {
+                title: id.into(),
+                imdb_id: id.into(),
+                media_type: MediaType::MOVIE,
+                year: Year(2000..=2000),
+                poster: None,
+            };
+            let config = TuiConfig::default();
+            let mut list = StatefulList::new(
+                ["tt1", "tt2", "tt3", "tt4"].map(result).to_vec(),
+                &config,
+            );
+            // Going round from the top
+            assert_eq!(list.neighbours(), ["tt2", "tt4"]);
+            list.next();
+            assert_eq!(list.neighbours(), ["tt3", "tt1"]);
+            // Ones already fetched aren't asked for again
+            list.entry_paragraphs[2] = Some(Paragraph::new(""));
+            assert_eq!(list.neighbours(), ["tt1"]);
+            // Either side is the same result, or there's nothing else
+            let two =
+                StatefulList::new(vec![result("tt1"), result("tt2")], &config);
+            assert_eq!(two.neighbours(), ["tt2"]);
+            let one = StatefulList::new(vec![result("tt1")], &config);
+            assert!(one.neighbours().is_empty());
+
+            let (started, starts) = mpsc::channel();
+            let (release, gate) = mpsc::channel::<()>();
+            let fetches = Arc::new(Mutex::new(Vec::new()));
+            let fetched = Arc::clone(&fetches);
+            let prefetcher = Prefetcher::new(move |imdb_id| {
+                started.send(()).unwrap();
+                if imdb_id == "tt1" {
+                    gate.recv().unwrap();
+                }
+                fetched.lock().unwrap().push(imdb_id.to_owned());
+                let json = format!(
+                    r#"{{"Title":"{imdb_id}","Year":"2000","Runtime":"N/A","Genre":"N/A","Director":"N/A","Writer":"N/A","Actors":"N/A","Plot":"N/A","Language":"N/A","Country":"N/A","imdbRating":"N/A","imdbVotes":"N/A","imdbID":"{imdb_id}","Type":"movie","Response":"True"}}"#
+                );
+                Ok(serde_json::from_str::<Entry>(&json).unwrap())
+            });
+            prefetcher.want(vec!["tt1".into(), "tt2".into()]);
+            starts.recv().unwrap();
+            // Moving away while tt1 is being fetched means tt2 is skipped
+            prefetcher.want(vec!["tt3".into()]);
+            release.send(()).unwrap();
+            let deadline = Instant::now() + Duration::from_secs(5);
+            let tt3 = loop {
+                if let Some(entry) = prefetcher.take("tt3") {
+                    break entry;
+                }
+                assert!(Instant::now() < deadline, "tt3 was never fetched");
+                thread::sleep(Duration::from_millis(10));
+            };
+            assert_eq!(tt3.imdb_id, "tt3");
+            assert_eq!(*fetches.lock().unwrap(), ["tt1", "tt3"]);
+            // Entries are only handed over once
+            assert!(prefetcher.take("tt1").is_some());
+            assert!(prefetcher.take("tt1").is_none());
+            assert!(prefetcher.take("tt2").is_none());
+
+            // Settling on an entry that's still being fetched waits for it,
+            // rather than fetching it a second time
+            starts.try_iter().for_each(drop);
+            prefetcher.want(vec!["tt1".into()]);
+            starts.recv().unwrap();
+            let waiter = thread::spawn({
+                let release = release.clone();
+                move || {
+                    thread::sleep(Duration::from_millis(50));
+                    release.send(()).unwrap();
+                }
+            });
+            let tt1 = prefetcher.wait_for("tt1").expect("tt1 was fetched");
+            waiter.join().unwrap();
+            assert_eq!(tt1.imdb_id, "tt1");
+            assert!(prefetcher.take("tt1").is_none());
+            // Ones that aren't being fetched are left to the caller
+            assert!(prefetcher.wait_for("tt4").is_none());
+            assert_eq!(*fetches.lock().unwrap(), ["tt1", "tt3", "tt1"]);
+        }
+
+        #[test]
+        fn debouncing() {
+            let start = Instant::now();
+            let at = |millis| start + Duration::from_millis(millis);
+            let mut debounce = Debounce::new(0);
+            // The first result is fetched straight away
+            assert!(debounce.settled(0, start));
+            assert_eq!(debounce.remaining(start), None);
+            // Scrolling quickly past results doesn't fetch any of them
+            assert!(!debounce.settled(1, at(10)));
+            assert!(!debounce.settled(2, at(60)));
+            assert!(!debounce.settled(3, at(110)));
+            assert!(!debounce.settled(3, at(200)));
+            assert_eq!(
+                debounce.remaining(at(200)),
+                Some(SETTLE - (at(200) - at(110)))
+            );
+            // Until the selection stays put
+            assert!(debounce.settled(3, at(260)));
+            assert_eq!(debounce.remaining(at(260)), None);
+            assert!(debounce.settled(3, at(270)));
+            // Checking late leaves nothing to wait for
+            assert!(!debounce.settled(4, at(300)));
+            assert_eq!(debounce.remaining(at(1000)), Some(Duration::ZERO));
+        }
+
+        #[test]
+        fn ctrl_c_quits() {
+            let key = |code, modifiers| KeyEvent::new(code, modifiers);
+            let c = KeyCode::Char('c');
+            assert!(is_interrupt(&key(c, KeyModifiers::CONTROL)));
+            assert!(is_interrupt(&key(
+                c,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )));
+            // Just c, or Ctrl with something else, is a normal key press
+            assert!(!is_interrupt(&key(c, KeyModifiers::NONE)));
+            assert!(!is_interrupt(&key(c, KeyModifiers::ALT)));
+            assert!(!is_interrupt(&key(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL
+            )));
+        }
+
+        #[test]
+        fn guard_restores() {
+            struct Recorder(Rc<Cell<usize>>);
+
+            impl Restore for Recorder {
+                fn restore(&mut self) -> io::Result<()> {
+                    self.0.set(self.0.get() + 1);
+                    Ok(())
+                }
+            }
+
+            let restores = Rc::new(Cell::new(0));
+            // Dropped without finishing, like on an early return
+            drop(TerminalGuard::new(Recorder(Rc::clone(&restores))));
+            assert_eq!(restores.get(), 1);
+            // Finishing restores once, not again when dropped
+            let guard = TerminalGuard::new(Recorder(Rc::clone(&restores)));
+            guard.finish().unwrap();
+            assert_eq!(restores.get(), 2);
+            // Unwinding from a panic
+            let recorder = Recorder(Rc::clone(&restores));
+            let panicked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                let _guard = TerminalGuard::new(recorder);
+                panic!("in the TUI");
+            }));
+            assert!(panicked.is_err());
+            assert_eq!(restores.get(), 3);
+        }
+
+        #[test]
+        fn restored_before_panic_message() {
+            // Other tests panic too, so only this thread's panics are noted
+            let this_thread = thread::current().id();
+            let events = Arc::new(Mutex::new(Vec::new()));
+            let note = {
+                let events = Arc::clone(&events);
+                move |event| {
+                    if thread::current().id() == this_thread {
+                        events.lock().unwrap().push(event);
+                    }
+                }
+            };
+            let original = std::panic::take_hook();
+            // Stands in for the default hook, which prints the message
+            let printer = note.clone();
+            std::panic::set_hook(Box::new(move |_| printer("printed")));
+
+            let restore = PanicRestore::install(move || note("restored"));
+            let panicked = std::panic::catch_unwind(|| panic!("in the TUI"));
+            assert!(panicked.is_err());
+            // Once the TUI is done, panics go straight to the previous hook
+            drop(restore);
+            let _ = std::panic::catch_unwind(|| panic!("after the TUI"));
+            std::panic::set_hook(original);
+
+            assert_eq!(
+                *events.lock().unwrap(),
+                ["restored", "printed", "printed"]
+            );
+        }
     }
 }